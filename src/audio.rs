@@ -0,0 +1,350 @@
+use bevy::{
+    asset::{HandleId, LoadState},
+    audio::AudioSink,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    menu::Volume,
+    player::Player,
+    ship::{
+        health::{ShipDamaged, ShipDestroyed},
+        CurrentSpeeds, MaxSpeeds,
+    },
+    GameState,
+};
+
+// Volume settings run from 0 to this
+pub const MAX_LEVEL: u32 = 9;
+const ENGINE_HUM: &str = "audio/engine_hum.ogg";
+// Playback rate of the hum at rest and at top speed
+const ENGINE_IDLE_RATE: f32 = 0.8;
+const ENGINE_TOP_RATE: f32 = 1.6;
+
+// Per channel levels, scaled again by the master `Volume`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioChannels {
+    pub music: u32,
+    pub effects: u32,
+}
+
+impl Default for AudioChannels {
+    fn default() -> AudioChannels {
+        AudioChannels {
+            music: MAX_LEVEL,
+            effects: MAX_LEVEL,
+        }
+    }
+}
+
+impl AudioChannels {
+    pub fn music_gain(&self, master: Volume) -> f32 {
+        level_gain(master.0) * level_gain(self.music)
+    }
+
+    pub fn effects_gain(&self, master: Volume) -> f32 {
+        level_gain(master.0) * level_gain(self.effects)
+    }
+}
+
+fn level_gain(level: u32) -> f32 {
+    level.min(MAX_LEVEL) as f32 / MAX_LEVEL as f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MusicTrack {
+    Menu,
+    Game,
+    Pause,
+}
+
+impl MusicTrack {
+    const ALL: [MusicTrack; 3] = [MusicTrack::Menu, MusicTrack::Game, MusicTrack::Pause];
+
+    fn for_state(state: &GameState) -> MusicTrack {
+        match state {
+            GameState::Splash | GameState::Menu | GameState::Loading => MusicTrack::Menu,
+            GameState::Game => MusicTrack::Game,
+            GameState::Paused => MusicTrack::Pause,
+        }
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            MusicTrack::Menu => "audio/music/menu.ogg",
+            MusicTrack::Game => "audio/music/game.ogg",
+            MusicTrack::Pause => "audio/music/pause.ogg",
+        }
+    }
+}
+
+// One-shot sounds, sent as events by whatever causes them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Click,
+    Fire,
+    Hit,
+    Explosion,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 4] = [
+        SoundEffect::Click,
+        SoundEffect::Fire,
+        SoundEffect::Hit,
+        SoundEffect::Explosion,
+    ];
+
+    fn path(self) -> &'static str {
+        match self {
+            SoundEffect::Click => "audio/sfx/click.ogg",
+            SoundEffect::Fire => "audio/sfx/fire.ogg",
+            SoundEffect::Hit => "audio/sfx/hit.ogg",
+            SoundEffect::Explosion => "audio/sfx/explosion.ogg",
+        }
+    }
+}
+
+// Loaded up front so the first click or shot does not wait on the disk
+#[derive(Resource)]
+struct SoundLibrary {
+    music: HashMap<MusicTrack, Handle<AudioSource>>,
+    engine_hum: Handle<AudioSource>,
+    effects: HashMap<SoundEffect, Handle<AudioSource>>,
+}
+
+#[derive(Default, Resource)]
+struct MusicPlayer {
+    current: Option<MusicTrack>,
+    sinks: HashMap<MusicTrack, Handle<AudioSink>>,
+}
+
+#[derive(Default, Resource)]
+struct EngineHum(Option<Handle<AudioSink>>);
+
+// Sounds that failed to load, so each is only reported once
+#[derive(Default, Resource)]
+struct MissingSounds(HashSet<HandleId>);
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioChannels>()
+            .init_resource::<MusicPlayer>()
+            .init_resource::<EngineHum>()
+            .init_resource::<MissingSounds>()
+            .add_event::<SoundEffect>()
+            .add_startup_system(load_sounds)
+            .add_system(play_music)
+            .add_system(play_engine_hum)
+            .add_system(button_click_sound.before(play_sound_effects))
+            .add_system(damage_sounds.before(play_sound_effects))
+            .add_system(play_sound_effects);
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundLibrary {
+        music: MusicTrack::ALL
+            .into_iter()
+            .map(|track| (track, asset_server.load(track.path())))
+            .collect(),
+        engine_hum: asset_server.load(ENGINE_HUM),
+        effects: SoundEffect::ALL
+            .into_iter()
+            .map(|effect| (effect, asset_server.load(effect.path())))
+            .collect(),
+    });
+}
+
+// Bevy's Audio retries a source every frame until it loads, and one that never does stays
+// queued for good. Sounds are only handed over once this says Loaded.
+fn sound_state(
+    handle: &Handle<AudioSource>,
+    asset_server: &AssetServer,
+    missing: &mut MissingSounds,
+) -> LoadState {
+    let state = asset_server.get_load_state(handle);
+    if state == LoadState::Failed && missing.0.insert(handle.id) {
+        warn!(
+            "Unable to load sound {:?}, it will not play",
+            asset_server.get_handle_path(handle)
+        );
+    }
+    state
+}
+
+// The gameplay track is only paused under the pause menu, so resuming picks it up where it left
+// off. Any other change of track stops everything else.
+fn play_music(
+    state: Res<State<GameState>>,
+    volume: Res<Volume>,
+    channels: Res<AudioChannels>,
+    library: Res<SoundLibrary>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<MusicPlayer>,
+    mut missing: ResMut<MissingSounds>,
+) {
+    let gain = channels.music_gain(*volume);
+    let track = MusicTrack::for_state(state.current());
+    let source = &library.music[&track];
+    // Switch once the new track has loaded, or to silence if it never will
+    let load_state = sound_state(source, &asset_server, &mut missing);
+    let switchable = music.sinks.contains_key(&track)
+        || matches!(load_state, LoadState::Loaded | LoadState::Failed);
+
+    if music.current != Some(track) && switchable {
+        for (playing, handle) in &music.sinks {
+            if let Some(sink) = sinks.get(handle) {
+                if track == MusicTrack::Pause {
+                    sink.pause();
+                } else if *playing != track {
+                    sink.stop();
+                }
+            }
+        }
+        if track != MusicTrack::Pause {
+            music.sinks.retain(|playing, _| *playing == track);
+        }
+        match music.sinks.get(&track) {
+            Some(handle) => {
+                if let Some(sink) = sinks.get(handle) {
+                    sink.play();
+                }
+            }
+            None if load_state == LoadState::Loaded => {
+                let sink = audio
+                    .play_with_settings(source.clone(), PlaybackSettings::LOOP.with_volume(gain));
+                music.sinks.insert(track, sinks.get_handle(sink));
+            }
+            None => {}
+        }
+        music.current = Some(track);
+    }
+
+    if volume.is_changed() || channels.is_changed() {
+        for handle in music.sinks.values() {
+            if let Some(sink) = sinks.get(handle) {
+                sink.set_volume(gain);
+            }
+        }
+    }
+}
+
+// Idles low while sitting still and rises with the player's speed
+fn engine_rate(speeds: &CurrentSpeeds, max_speeds: &MaxSpeeds) -> f32 {
+    let top_speed = max_speeds.max_speeds.length();
+    if top_speed <= 0.0 {
+        return ENGINE_IDLE_RATE;
+    }
+    let fraction = (speeds.current_speeds.length() / top_speed).clamp(0.0, 1.0);
+    ENGINE_IDLE_RATE + (ENGINE_TOP_RATE - ENGINE_IDLE_RATE) * fraction
+}
+
+fn play_engine_hum(
+    state: Res<State<GameState>>,
+    volume: Res<Volume>,
+    channels: Res<AudioChannels>,
+    library: Res<SoundLibrary>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    sinks: Res<Assets<AudioSink>>,
+    mut hum: ResMut<EngineHum>,
+    mut missing: ResMut<MissingSounds>,
+    player_query: Query<(&CurrentSpeeds, &MaxSpeeds), With<Player>>,
+) {
+    let gain = channels.effects_gain(*volume);
+    match (state.current(), hum.0.clone()) {
+        (GameState::Game, None)
+            if sound_state(&library.engine_hum, &asset_server, &mut missing)
+                == LoadState::Loaded =>
+        {
+            let sink = audio.play_with_settings(
+                library.engine_hum.clone(),
+                PlaybackSettings::LOOP
+                    .with_volume(gain)
+                    .with_speed(ENGINE_IDLE_RATE),
+            );
+            hum.0 = Some(sinks.get_handle(sink));
+        }
+        (GameState::Game, Some(handle)) => {
+            if let Some(sink) = sinks.get(&handle) {
+                sink.play();
+                sink.set_volume(gain);
+                if let Ok((speeds, max_speeds)) = player_query.get_single() {
+                    sink.set_speed(engine_rate(speeds, max_speeds));
+                }
+            }
+        }
+        (GameState::Paused, Some(handle)) => {
+            if let Some(sink) = sinks.get(&handle) {
+                sink.pause();
+            }
+        }
+        (_, Some(handle)) => {
+            if let Some(sink) = sinks.get(&handle) {
+                sink.stop();
+            }
+            hum.0 = None;
+        }
+        _ => {}
+    }
+}
+
+fn button_click_sound(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Clicked {
+            sounds.send(SoundEffect::Click);
+        }
+    }
+}
+
+// A destroyed ship only explodes, it does not also play the hit
+fn damage_sounds(
+    mut damaged: EventReader<ShipDamaged>,
+    mut destroyed: EventReader<ShipDestroyed>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    let destroyed: Vec<Entity> = destroyed.iter().map(|event| event.ship).collect();
+    for event in damaged.iter() {
+        if !destroyed.contains(&event.ship) {
+            sounds.send(SoundEffect::Hit);
+        }
+    }
+    for _ in &destroyed {
+        sounds.send(SoundEffect::Explosion);
+    }
+}
+
+fn play_sound_effects(
+    mut sounds: EventReader<SoundEffect>,
+    volume: Res<Volume>,
+    channels: Res<AudioChannels>,
+    library: Res<SoundLibrary>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+    mut missing: ResMut<MissingSounds>,
+) {
+    let gain = channels.effects_gain(*volume);
+    if gain <= 0.0 {
+        sounds.clear();
+        return;
+    }
+    for effect in sounds.iter() {
+        let source = &library.effects[effect];
+        // A shot fired before its sound has loaded goes unheard rather than queueing up
+        if sound_state(source, &asset_server, &mut missing) != LoadState::Loaded {
+            continue;
+        }
+        audio.play_with_settings(source.clone(), PlaybackSettings::ONCE.with_volume(gain));
+    }
+}
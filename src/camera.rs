@@ -1,11 +1,26 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
-use crate::ship;
+use crate::{
+    rail::{RailPath, RailPosition},
+    ship,
+    simulation::{SimulatedTransform, SIMULATION_DT},
+};
 
 #[derive(Component)]
 pub struct CameraTracker;
 
+// Where the game camera sits relative to the tracker it hangs off
+pub const CAMERA_OFFSET: Vec3 = Vec3::new(0.0, 8.0, -30.0);
+
+// Rate the follow was tuned at, when it closed a fixed share of the gap every frame
+const FOLLOW_TUNED_HZ: f32 = 60.0;
+
+// Share of the gap to close over `dt`, equivalent to closing 1 / `frames` of it per 60 Hz frame
+fn follow_fraction(frames: f32, dt: f32) -> f32 {
+    1.0 - (1.0 - 1.0 / frames).powf(dt * FOLLOW_TUNED_HZ)
+}
+
 pub fn setup_camera(mut commands: Commands) {
     commands
         .spawn(Collider::capsule_x(2.0, 6.0))
@@ -14,23 +29,46 @@ pub fn setup_camera(mut commands: Commands) {
         .insert(Sensor::default())
         // .insert(ActiveEvents::COLLISION_EVENTS)
         .insert(TransformBundle::from(Transform::from_xyz(0.0, 0.0, 0.0)))
+        .insert(SimulatedTransform::default())
+        .insert(RailPosition::default())
         .with_children(|p| {
             p.spawn(Camera3dBundle {
-                transform: Transform::from_xyz(0.0, 8.0, -30.0).looking_at(Vec3::ZERO, Vec3::Y),
+                transform: Transform::from_translation(CAMERA_OFFSET)
+                    .looking_at(Vec3::ZERO, Vec3::Y),
                 ..Default::default()
             });
         })
         .insert(CameraTracker);
 }
 
+// The tracker outlives a level, so it is pulled back to the start whenever one begins
+pub fn reset_camera(
+    mut query: Query<
+        (&mut Transform, &mut SimulatedTransform, &mut RailPosition),
+        With<CameraTracker>,
+    >,
+) {
+    for (mut transform, mut simulated, mut rail_position) in &mut query {
+        *transform = Transform::from_xyz(0.0, 0.0, 0.0);
+        simulated.teleport(*transform);
+        *rail_position = RailPosition::default();
+    }
+}
+
+// Runs once per simulation tick, after the ships have moved. The follow happens in the rail's
+// frame, so the camera turns with the rail rather than cutting corners.
 pub fn move_camera_system(
     player_query: Query<
-        (&Transform, &ship::MaxSpeeds),
+        (&RailPosition, &ship::CurrentSpeeds),
         (With<crate::player::Player>, Without<CameraTracker>),
     >,
-    mut cam_query: Query<(&mut Transform, Entity), With<CameraTracker>>,
+    mut cam_query: Query<(&mut SimulatedTransform, Entity, &mut RailPosition), With<CameraTracker>>,
+    rail_query: Query<&RailPath>,
     narrow_phase: Res<RapierContext>,
 ) {
+    let Ok(rail) = rail_query.get_single() else {
+        return;
+    };
     let cam_query_res = cam_query.get_single_mut();
     let player_query_res = player_query.get_single();
 
@@ -49,15 +87,23 @@ pub fn move_camera_system(
             // }
         }
         if !intersecting {
-            let mut move_distance = player_query.0.translation - cam_query.0.translation; // (player_query.translation - cam_query.0.translation) * Vec3::new(2.0, 2.0, 2.0);
-            move_distance.z = player_query.1.max_speeds.z;
+            let mut move_distance = player_query.0.local - cam_query.2.local; // (player_query.translation - cam_query.0.translation) * Vec3::new(2.0, 2.0, 2.0);
+            let dt = SIMULATION_DT;
+            move_distance.z = player_query.1.current_speeds.z * dt;
             let mut reduction_factor = 25.0;
             if move_distance.x.abs() > 10.0 || move_distance.y.abs() > 5.0 {
                 reduction_factor = 10.0;
             }
 
-            cam_query.0.translation +=
-                move_distance / Vec3::new(reduction_factor * 2.0, reduction_factor, 1.0);
+            cam_query.2.local += move_distance
+                * Vec3::new(
+                    follow_fraction(reduction_factor * 2.0, dt),
+                    follow_fraction(reduction_factor, dt),
+                    1.0,
+                );
+            let frame = rail.frame(cam_query.2.local.z);
+            cam_query.0.current.translation = frame.place(cam_query.2.local.truncate());
+            cam_query.0.current.rotation = frame.rotation;
         }
     }
 }
@@ -0,0 +1,186 @@
+use bevy::{asset::FileAssetIo, prelude::*};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ship::health::{HullSettings, ShieldSettings},
+    weapon::WeaponSettings,
+};
+
+// Every hull directory under assets/ships carries a <Ship>.ship.ron definition
+pub const SHIP_DEFINITION_EXTENSION: &str = "ship.ron";
+pub const SHIPS_DIR: &str = "ships";
+const DEFAULT_SHIP: &str = "Pancake";
+// Colour baked into the glTF exports
+const DEFAULT_SKIN: &str = "Orange";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShipTexture {
+    pub name: String,
+    pub path: String,
+}
+
+// How the physics shape is derived from the collider mesh
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum ColliderMode {
+    // Single hull around every vertex, cheapest but fills in gaps between wings
+    #[default]
+    ConvexHull,
+    // V-HACD split into convex parts, cached on disk after the first build
+    ConvexDecomposition {
+        resolution: u32,
+        concavity: f32,
+    },
+    // Capsule fuselage plus a flat box for the wings, fitted to the mesh bounds
+    Simplified,
+    // Exact triangles, most accurate and most expensive
+    Trimesh,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColliderSettings {
+    // Asset path of the mesh the physics collider is built from
+    pub mesh: String,
+    #[serde(default)]
+    pub mode: ColliderMode,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShipDefinition {
+    // Directory name under assets/ships, filled in by the catalog scan
+    #[serde(skip)]
+    pub id: String,
+    pub name: String,
+    // Scene path for hulls that ship a glTF export
+    pub model: Option<String>,
+    pub textures: Vec<ShipTexture>,
+    // Units per second and units per second squared
+    pub max_speeds: Vec3,
+    pub accelerations: Vec3,
+    pub bank_angle: f32,
+    pub collider: ColliderSettings,
+    // Hulls without a `weapon` entry carry the standard energy cannon
+    #[serde(default)]
+    pub weapon: WeaponSettings,
+    #[serde(default)]
+    pub hull: HullSettings,
+    #[serde(default)]
+    pub shield: ShieldSettings,
+}
+
+// Catalog id of the hull the player flies, chosen in the hangar
+#[derive(Debug, Clone, PartialEq, Eq, Resource)]
+pub struct SelectedShip(pub String);
+
+// Texture variant name applied to the selected hull, e.g. "Red"
+#[derive(Debug, Clone, PartialEq, Eq, Resource)]
+pub struct SelectedSkin(pub String);
+
+impl ShipDefinition {
+    // Asset path of this definition, which the collider loader reads from
+    pub fn definition_path(&self) -> String {
+        format!(
+            "{}/{id}/{id}.{}",
+            SHIPS_DIR,
+            SHIP_DEFINITION_EXTENSION,
+            id = self.id
+        )
+    }
+
+    pub fn texture(&self, name: &str) -> Option<&ShipTexture> {
+        self.textures.iter().find(|texture| texture.name == name)
+    }
+
+    // The skin a newly picked hull starts in
+    pub fn default_texture(&self) -> Option<&ShipTexture> {
+        self.texture(DEFAULT_SKIN).or_else(|| self.textures.first())
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct ShipCatalog {
+    ships: Vec<ShipDefinition>,
+}
+
+impl ShipCatalog {
+    // Reads every <ship>/ship.ron below `ships_dir`, skipping hulls without a valid definition
+    pub fn scan(ships_dir: &Path) -> ShipCatalog {
+        let entries = match fs::read_dir(ships_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                error!("Unable to read ship directory {:?}: {}", ships_dir, err);
+                return ShipCatalog::default();
+            }
+        };
+
+        let mut ships: Vec<ShipDefinition> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| {
+                let id = entry.file_name().to_string_lossy().to_string();
+                let definition_path = entry
+                    .path()
+                    .join(format!("{}.{}", id, SHIP_DEFINITION_EXTENSION));
+                match read_definition(&definition_path) {
+                    Ok(mut definition) => {
+                        definition.id = id;
+                        Some(definition)
+                    }
+                    Err(err) => {
+                        warn!("Skipping ship {}: {}", id, err);
+                        None
+                    }
+                }
+            })
+            .collect();
+        ships.sort_by(|a, b| a.id.cmp(&b.id));
+
+        ShipCatalog { ships }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ShipDefinition> {
+        self.ships.iter().find(|ship| ship.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ShipDefinition> {
+        self.ships.iter()
+    }
+
+    // Falls back to the first hull on disk when the default one is missing
+    pub fn default_ship(&self) -> Option<&ShipDefinition> {
+        self.get(DEFAULT_SHIP).or_else(|| self.ships.first())
+    }
+}
+
+fn read_definition(path: &Path) -> Result<ShipDefinition, String> {
+    let contents = fs::read_to_string(path).map_err(|err| format!("{:?}: {}", path, err))?;
+    ron::from_str(&contents).map_err(|err| format!("{:?}: {}", path, err))
+}
+
+// Resolves the on-disk assets directory the same way the default AssetServer does
+pub fn assets_dir() -> PathBuf {
+    FileAssetIo::get_base_path().join("assets")
+}
+
+// A selection restored from the settings file is kept while its hull and skin still exist
+pub fn load_ship_catalog(
+    mut commands: Commands,
+    saved_ship: Option<Res<SelectedShip>>,
+    saved_skin: Option<Res<SelectedSkin>>,
+) {
+    let catalog = ShipCatalog::scan(&assets_dir().join(SHIPS_DIR));
+    info!("Loaded {} ships into the catalog", catalog.ships.len());
+    let ship = saved_ship
+        .and_then(|saved| catalog.get(&saved.0))
+        .or_else(|| catalog.default_ship());
+    let skin = saved_skin
+        .filter(|saved| ship.map_or(false, |ship| ship.texture(&saved.0).is_some()))
+        .map_or(DEFAULT_SKIN.to_string(), |saved| saved.0.clone());
+    let ship = ship.map_or(DEFAULT_SHIP.to_string(), |ship| ship.id.clone());
+    commands.insert_resource(SelectedShip(ship));
+    commands.insert_resource(SelectedSkin(skin));
+    commands.insert_resource(catalog);
+}
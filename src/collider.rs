@@ -0,0 +1,274 @@
+use bevy::{
+    asset::{AssetLoader, FileAssetIo, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    utils::BoxedFuture,
+};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::{
+    catalog::{ColliderMode, ShipDefinition, SHIP_DEFINITION_EXTENSION},
+    obj::{self, ObjMesh},
+};
+
+// Decomposed hulls are written here so V-HACD only runs once per mesh and parameter set
+const COLLIDER_CACHE_DIR: &str = ".cache/colliders";
+// Bumped whenever DecompositionCache or the decomposition itself changes, orphaning old entries
+const COLLIDER_CACHE_VERSION: u32 = 1;
+
+// Physics shape for a hull, built off the main thread by the asset server
+#[derive(TypeUuid)]
+#[uuid = "3c6f1e0a-8d57-4b7a-9f43-2a9d1b6e5c21"]
+pub struct ShipColliderAsset {
+    pub collider: Collider,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DecompositionCache {
+    parts: Vec<Vec<Vec3>>,
+}
+
+// Loads a <Ship>.ship.ron definition and builds the collider its settings ask for
+#[derive(Default)]
+pub struct ShipColliderLoader;
+
+impl AssetLoader for ShipColliderLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let definition: ShipDefinition = ron::de::from_bytes(bytes)?;
+            let settings = definition.collider;
+            let mesh_bytes = load_context.read_asset_bytes(&settings.mesh).await?;
+            let mesh = obj::parse_obj(mesh_bytes.as_slice())?;
+
+            let collider = match settings.mode {
+                ColliderMode::ConvexHull => convex_hull(&mesh),
+                ColliderMode::ConvexDecomposition {
+                    resolution,
+                    concavity,
+                } => {
+                    let params = VHACDParameters {
+                        resolution,
+                        concavity,
+                        ..default()
+                    };
+                    convex_decomposition(
+                        &mesh,
+                        &params,
+                        cache_path(&settings.mesh, &mesh_bytes, &params),
+                    )
+                }
+                ColliderMode::Simplified => simplified(&mesh),
+                ColliderMode::Trimesh => {
+                    Collider::trimesh(mesh.positions.clone(), mesh.position_indices())
+                }
+            };
+            load_context.set_default_asset(LoadedAsset::new(ShipColliderAsset { collider }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[SHIP_DEFINITION_EXTENSION]
+    }
+}
+
+// Draws an OBJ file directly, for hulls without a glTF export to show
+#[derive(Default)]
+pub struct ObjMeshLoader;
+
+impl AssetLoader for ObjMeshLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let vertices = obj::parse_obj(bytes)?.flat_vertices();
+            let count = vertices.positions.len() as u32;
+            let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices.positions);
+            mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vertices.normals);
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vertices.uvs);
+            mesh.set_indices(Some(Indices::U32((0..count).collect())));
+            load_context.set_default_asset(LoadedAsset::new(mesh));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+}
+
+fn convex_hull(mesh: &ObjMesh) -> Collider {
+    // A hull only fails on degenerate input, where the raw triangles are still usable
+    Collider::convex_hull(&mesh.positions)
+        .unwrap_or_else(|| Collider::trimesh(mesh.positions.clone(), mesh.position_indices()))
+}
+
+fn convex_decomposition(mesh: &ObjMesh, params: &VHACDParameters, cache: PathBuf) -> Collider {
+    if let Some(parts) = fs::read_to_string(&cache)
+        .ok()
+        .and_then(|contents| ron::from_str::<DecompositionCache>(&contents).ok())
+    {
+        return compound_from_parts(&parts.parts).unwrap_or_else(|| convex_hull(mesh));
+    }
+
+    let collider = Collider::convex_decomposition_with_params(
+        &mesh.positions,
+        &mesh.position_indices(),
+        params,
+    );
+    let parts = decomposition_parts(&collider);
+    if parts.is_empty() {
+        warn!("Convex decomposition produced no parts, using a single hull");
+        return convex_hull(mesh);
+    }
+
+    let written = fs::create_dir_all(cache.parent().unwrap_or(&cache))
+        .map_err(|err| err.to_string())
+        .and_then(|_| ron::to_string(&DecompositionCache { parts }).map_err(|err| err.to_string()))
+        .and_then(|contents| fs::write(&cache, contents).map_err(|err| err.to_string()));
+    if let Err(err) = written {
+        warn!("Unable to cache collider at {:?}: {}", cache, err);
+    }
+    collider
+}
+
+// Pulls the hull vertices back out of a decomposed compound so they can be cached
+fn decomposition_parts(collider: &Collider) -> Vec<Vec<Vec3>> {
+    let Some(compound) = collider.raw.as_compound() else {
+        return vec![];
+    };
+    compound
+        .shapes()
+        .iter()
+        .filter_map(|(isometry, shape)| {
+            shape.as_convex_polyhedron().map(|polyhedron| {
+                polyhedron
+                    .points()
+                    .iter()
+                    .map(|point| {
+                        let point = isometry * point;
+                        Vec3::new(point.x, point.y, point.z)
+                    })
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+fn compound_from_parts(parts: &[Vec<Vec3>]) -> Option<Collider> {
+    let shapes: Vec<(Vect, Rot, Collider)> = parts
+        .iter()
+        .filter_map(|points| Collider::convex_hull(points))
+        .map(|collider| (Vec3::ZERO, Quat::IDENTITY, collider))
+        .collect();
+    if shapes.is_empty() {
+        return None;
+    }
+    Some(Collider::compound(shapes))
+}
+
+fn simplified(mesh: &ObjMesh) -> Collider {
+    let (min, max) = mesh.positions.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), position| (min.min(*position), max.max(*position)),
+    );
+    let center = (min + max) / 2.0;
+    let half_extents = ((max - min) / 2.0).max(Vec3::splat(0.01));
+
+    // Fuselage runs along z, the wing box spans the full width but stays thin
+    let radius = half_extents.y.min(half_extents.x);
+    let fuselage = Collider::capsule_z((half_extents.z - radius).max(0.0), radius);
+    let wings = Collider::cuboid(half_extents.x, half_extents.y * 0.25, half_extents.z * 0.5);
+    Collider::compound(vec![
+        (center, Quat::IDENTITY, fuselage),
+        (center, Quat::IDENTITY, wings),
+    ])
+}
+
+// 64-bit FNV-1a. The cache outlives the build, so the key can't come from std's hashers, whose
+// output may change between Rust releases.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+// Keyed on the mesh contents and parameters so edited meshes get rebuilt
+fn cache_path(mesh_path: &str, mesh_bytes: &[u8], params: &VHACDParameters) -> PathBuf {
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, mesh_bytes);
+    hash = fnv1a(hash, &params.resolution.to_le_bytes());
+    hash = fnv1a(hash, &params.concavity.to_bits().to_le_bytes());
+    let name = mesh_path.replace(['/', '\\', '.'], "_");
+
+    FileAssetIo::get_base_path()
+        .join(COLLIDER_CACHE_DIR)
+        .join(format!(
+            "{}-v{}-{:016x}.ron",
+            name, COLLIDER_CACHE_VERSION, hash
+        ))
+}
+
+pub struct ShipColliderPlugin;
+
+impl Plugin for ShipColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ShipColliderAsset>()
+            .init_asset_loader::<ShipColliderLoader>()
+            .init_asset_loader::<ObjMeshLoader>()
+            .add_system(attach_ship_collider_system);
+    }
+}
+
+// Inserts the Collider once the entity's collider asset has finished loading
+pub fn attach_ship_collider_system(
+    mut commands: Commands,
+    query: Query<(Entity, &Handle<ShipColliderAsset>), Without<Collider>>,
+    colliders: Res<Assets<ShipColliderAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, handle) in &query {
+        if let Some(asset) = colliders.get(handle) {
+            commands.entity(entity).insert(asset.collider.clone());
+        } else if asset_server.get_load_state(handle) == LoadState::Failed {
+            error!(
+                "Unable to load collider {:?}",
+                asset_server.get_handle_path(handle)
+            );
+            commands
+                .entity(entity)
+                .remove::<Handle<ShipColliderAsset>>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_hash_is_standard_fnv1a() {
+        // Reference values for 64-bit FNV-1a, the key must not drift between builds
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn cache_hash_can_be_fed_in_pieces() {
+        let whole = fnv1a(FNV_OFFSET_BASIS, b"meshparams");
+        let pieces = fnv1a(fnv1a(FNV_OFFSET_BASIS, b"mesh"), b"params");
+        assert_eq!(whole, pieces);
+    }
+}
@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::CollisionEvent;
+
+use crate::{
+    player::Player,
+    ship::{health::Hull, CurrentSpeeds, MaxSpeeds},
+    simulation::{self, SimulatedTransform, SimulationLabel, SimulationStage, SIMULATION_DT},
+    weapon::{Projectile, ProjectileHit},
+};
+
+// Seconds the player can't be hurt after taking a hit
+const INVULNERABLE_TIME: f32 = 1.5;
+// Sideways speed the player is thrown back at, as a fraction of its top strafe and climb speed
+const KNOCKBACK_STRENGTH: f32 = 1.0;
+
+// What a collider is, as far as contacts go. Anything without one of these (or a Hull) is
+// ignored, e.g. the camera tracker's sensor.
+
+// Scenery that hurts to fly into, usually together with a ContactDamage
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Obstacle;
+
+// Terrain, touching it always pushes the ship back up
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Ground;
+
+// Collected by flying through it
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Pickup;
+
+// Sensor volume that sets something off when a ship enters it, named so level logic can tell
+// them apart
+#[derive(Component, Debug, Clone, Default)]
+pub struct Trigger(pub String);
+
+// Damage and knockback are ignored until this runs out
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Invulnerable {
+    pub remaining: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ObstacleHit {
+    pub ship: Entity,
+    pub obstacle: Entity,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GroundHit {
+    pub ship: Entity,
+    pub ground: Entity,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PickupCollected {
+    pub ship: Entity,
+    pub pickup: Entity,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TriggerEntered {
+    pub ship: Entity,
+    pub trigger: Entity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContactKind {
+    Ship,
+    Obstacle,
+    Ground,
+    Pickup,
+    Trigger,
+    Projectile,
+}
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ObstacleHit>()
+            .add_event::<GroundHit>()
+            .add_event::<PickupCollected>()
+            .add_event::<TriggerEntered>()
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_run_criteria(simulation::game_running)
+                    .with_system(wear_off_invulnerability.after(SimulationLabel::Movement))
+                    // Contacts from this tick's physics step, answered before the next tick
+                    .with_system(dispatch_collisions.after(SimulationLabel::PhysicsStep))
+                    .with_system(knock_back_player.after(dispatch_collisions)),
+            );
+    }
+}
+
+fn contact_kind(
+    entity: Entity,
+    query: &Query<(
+        Option<&Hull>,
+        Option<&Obstacle>,
+        Option<&Ground>,
+        Option<&Pickup>,
+        Option<&Trigger>,
+        Option<&Projectile>,
+    )>,
+) -> Option<ContactKind> {
+    let (hull, obstacle, ground, pickup, trigger, projectile) = query.get(entity).ok()?;
+    if hull.is_some() {
+        Some(ContactKind::Ship)
+    } else if projectile.is_some() {
+        Some(ContactKind::Projectile)
+    } else if obstacle.is_some() {
+        Some(ContactKind::Obstacle)
+    } else if ground.is_some() {
+        Some(ContactKind::Ground)
+    } else if pickup.is_some() {
+        Some(ContactKind::Pickup)
+    } else if trigger.is_some() {
+        Some(ContactKind::Trigger)
+    } else {
+        None
+    }
+}
+
+// Sorts Rapier's raw contacts into gameplay events by what the two colliders are tagged as.
+// Hits on an invulnerable ship are dropped here, so nothing downstream has to check.
+pub fn dispatch_collisions(
+    mut collisions: EventReader<CollisionEvent>,
+    tag_query: Query<(
+        Option<&Hull>,
+        Option<&Obstacle>,
+        Option<&Ground>,
+        Option<&Pickup>,
+        Option<&Trigger>,
+        Option<&Projectile>,
+    )>,
+    projectile_query: Query<&Projectile>,
+    invulnerable_query: Query<(), With<Invulnerable>>,
+    player_query: Query<(), With<Player>>,
+    mut projectile_hits: EventWriter<ProjectileHit>,
+    mut obstacle_hits: EventWriter<ObstacleHit>,
+    mut ground_hits: EventWriter<GroundHit>,
+    mut pickups: EventWriter<PickupCollected>,
+    mut triggers: EventWriter<TriggerEntered>,
+) {
+    // A shot can touch two things in one step, it still only lands once. The player's
+    // invulnerability only starts next frame, so hits this frame are tracked here too.
+    let mut spent = Vec::new();
+    let mut hurt = Vec::new();
+    for collision in collisions.iter() {
+        let CollisionEvent::Started(a, b, _) = collision else {
+            continue;
+        };
+        for (entity, other) in [(*a, *b), (*b, *a)] {
+            let (Some(kind), Some(other_kind)) = (
+                contact_kind(entity, &tag_query),
+                contact_kind(other, &tag_query),
+            ) else {
+                continue;
+            };
+            let shielded = invulnerable_query.contains(entity) || hurt.contains(&entity);
+            match (kind, other_kind) {
+                (ContactKind::Projectile, ContactKind::Ship)
+                | (ContactKind::Projectile, ContactKind::Obstacle)
+                | (ContactKind::Projectile, ContactKind::Ground) => {
+                    let Ok(projectile) = projectile_query.get(entity) else {
+                        continue;
+                    };
+                    if other == projectile.owner
+                        || spent.contains(&entity)
+                        || invulnerable_query.contains(other)
+                        || hurt.contains(&other)
+                    {
+                        continue;
+                    }
+                    projectile_hits.send(ProjectileHit {
+                        projectile: entity,
+                        target: other,
+                        owner: projectile.owner,
+                        weapon_type: projectile.weapon_type,
+                        damage: projectile.damage,
+                    });
+                    spent.push(entity);
+                    if player_query.contains(other) {
+                        hurt.push(other);
+                    }
+                }
+                (ContactKind::Ship, ContactKind::Obstacle) if !shielded => {
+                    obstacle_hits.send(ObstacleHit {
+                        ship: entity,
+                        obstacle: other,
+                    });
+                    if player_query.contains(entity) {
+                        hurt.push(entity);
+                    }
+                }
+                (ContactKind::Ship, ContactKind::Ground) if !shielded => {
+                    ground_hits.send(GroundHit {
+                        ship: entity,
+                        ground: other,
+                    });
+                    if player_query.contains(entity) {
+                        hurt.push(entity);
+                    }
+                }
+                (ContactKind::Ship, ContactKind::Pickup) => pickups.send(PickupCollected {
+                    ship: entity,
+                    pickup: other,
+                }),
+                (ContactKind::Ship, ContactKind::Trigger) => triggers.send(TriggerEntered {
+                    ship: entity,
+                    trigger: other,
+                }),
+                _ => {}
+            }
+        }
+    }
+}
+
+// The player's body is kinematic, so Rapier won't push it out of whatever it hit. Instead it is
+// thrown away from the contact and given a moment of invulnerability to recover. Shots only
+// grant the invulnerability.
+fn knock_back_player(
+    mut commands: Commands,
+    mut obstacle_hits: EventReader<ObstacleHit>,
+    mut ground_hits: EventReader<GroundHit>,
+    mut projectile_hits: EventReader<ProjectileHit>,
+    obstacle_query: Query<&GlobalTransform>,
+    mut player_query: Query<(&SimulatedTransform, &mut CurrentSpeeds, &MaxSpeeds), With<Player>>,
+) {
+    // Where each hit came from, None for straight up
+    let obstacles = obstacle_hits.iter().map(|hit| {
+        let from = obstacle_query.get(hit.obstacle).ok();
+        (hit.ship, Some(from.map(|obstacle| obstacle.translation())))
+    });
+    let grounds = ground_hits.iter().map(|hit| (hit.ship, Some(None)));
+    let shots = projectile_hits.iter().map(|hit| (hit.target, None));
+
+    for (ship, shove) in obstacles.chain(grounds).chain(shots) {
+        let Ok((simulated, mut speeds, max_speeds)) = player_query.get_mut(ship) else {
+            continue;
+        };
+        if let Some(from) = shove {
+            // Speeds are in the rail's frame, which the ship is turned to
+            let direction = from
+                .and_then(|from| {
+                    let away = simulated.current.translation - from;
+                    (simulated.current.rotation.inverse() * away)
+                        .truncate()
+                        .try_normalize()
+                })
+                .unwrap_or(Vec2::Y);
+            let shove = direction * max_speeds.max_speeds.truncate() * KNOCKBACK_STRENGTH;
+            speeds.current_speeds.x = shove.x;
+            speeds.current_speeds.y = shove.y;
+        }
+        commands.entity(ship).insert(Invulnerable {
+            remaining: INVULNERABLE_TIME,
+        });
+    }
+}
+
+fn wear_off_invulnerability(mut commands: Commands, mut query: Query<(Entity, &mut Invulnerable)>) {
+    for (entity, mut invulnerable) in &mut query {
+        invulnerable.remaining -= SIMULATION_DT;
+        if invulnerable.remaining <= 0.0 {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
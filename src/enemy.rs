@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+use crate::{player::Player, rail::RailPosition, ship::ShipThrust, weapon::Weapon};
+
+// Distance down the rail to the player from which enemies open fire
+const FIRE_RANGE: f32 = 250.0;
+// Offset from the player's line that gets full steering, anything closer eases off
+const STEER_RANGE: f32 = 20.0;
+// Enemies that have flown this far past the player are removed
+const DESPAWN_DISTANCE: f32 = 100.0;
+
+#[derive(Component)]
+pub struct Enemy;
+
+// Runs once per simulation tick. Enemies line up with the player and hold the trigger while it
+// is ahead of them and in range.
+pub fn enemy_control_system(
+    player_query: Query<&RailPosition, With<Player>>,
+    mut enemy_query: Query<(&RailPosition, &mut ShipThrust, &mut Weapon), With<Enemy>>,
+) {
+    let player = player_query.get_single().ok();
+    for (position, mut thrust, mut weapon) in &mut enemy_query {
+        let Some(player) = player else {
+            thrust.thrust = Vec2::ZERO;
+            weapon.trigger = false;
+            continue;
+        };
+        let offset = player.local.truncate() - position.local.truncate();
+        thrust.thrust = (offset / STEER_RANGE).clamp(Vec2::NEG_ONE, Vec2::ONE);
+        let distance = position.local.z - player.local.z;
+        weapon.trigger = distance > 0.0 && distance < FIRE_RANGE;
+    }
+}
+
+pub fn despawn_passed_enemies(
+    mut commands: Commands,
+    player_query: Query<&RailPosition, With<Player>>,
+    enemy_query: Query<(Entity, &RailPosition), With<Enemy>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (entity, position) in &enemy_query {
+        if player.local.z - position.local.z > DESPAWN_DISTANCE {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
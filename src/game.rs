@@ -1,19 +1,28 @@
 use bevy::{asset, prelude::*};
 // use bevy::ecs::entity;
-use bevy::prelude::shape as bevy_shape;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::{NoUserData, RapierPhysicsPlugin};
 // use bevy_rapier3d::prelude::*;
 
 use super::{
-    camera, despawn_screen, menu::DisplayQuality, menu::Volume, player, ship, GameState,
-    MENU_TEXT_COLOR,
+    camera,
+    collision::CollisionPlugin,
+    despawn_screen, enemy,
+    level::LevelPlugin,
+    menu::DisplayQuality,
+    menu::Volume,
+    player, ship,
+    ship::health::HealthPlugin,
+    simulation::{self, SimulationLabel, SimulationPlugin, SimulationStage},
+    weapon::WeaponPlugin,
+    GameState,
 };
 
 pub struct GamePlugin;
 
+// Everything spawned for a level, despawned when leaving the game
 #[derive(Component)]
-struct OnGameScreen;
+pub struct OnGameScreen;
 
 #[derive(Deref, DerefMut, Resource)]
 struct GameTimer(Timer);
@@ -26,21 +35,41 @@ impl Plugin for GamePlugin {
         //         SystemSet::on_exit(GameState::Game).with_system(despawn_screen::<OnGameScreen>),
         //     );
 
-        app.insert_resource(Msaa { samples: 1 })
-            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+        // MSAA follows DisplayQuality, see quality.rs
+        // Rapier's systems run inside SimulationStage, see SimulationPlugin
+        let physics = RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false);
+        app.add_plugin(physics)
+            .add_plugin(SimulationPlugin)
+            .add_plugin(WeaponPlugin)
+            .add_plugin(HealthPlugin)
+            .add_plugin(CollisionPlugin)
+            .add_plugin(LevelPlugin)
             // .add_plugin(RapierDebugRenderPlugin::default())
             // .add_system_set(SystemSet::on_enter(GameState::Game).with_system(camera::setup_camera))
             .add_system_set(SystemSet::on_enter(GameState::Game).with_system(player::spawn_player))
-            .add_system_set(
-                SystemSet::on_update(GameState::Game).with_system(player::player_control_system),
-            )
-            .add_system_set(
-                SystemSet::on_update(GameState::Game).with_system(ship::move_ship_system),
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_run_criteria(simulation::game_running)
+                    .with_system(player::player_control_system.label(SimulationLabel::Input))
+                    .with_system(enemy::enemy_control_system.label(SimulationLabel::Input))
+                    .with_system(
+                        ship::move_ship_system
+                            .label(SimulationLabel::Movement)
+                            .after(SimulationLabel::Input),
+                    )
+                    .with_system(
+                        camera::move_camera_system
+                            .label(SimulationLabel::Camera)
+                            .after(SimulationLabel::Movement),
+                    )
+                    .with_system(enemy::despawn_passed_enemies.after(SimulationLabel::Movement)),
             )
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(camera::reset_camera))
             .add_system_set(
-                SystemSet::on_update(GameState::Game).with_system(camera::move_camera_system),
+                SystemSet::on_exit(GameState::Game).with_system(despawn_screen::<OnGameScreen>),
             )
-            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(test_setup));
+            .add_system(ship::apply_ship_skin_system);
         // .add_startup_system(camera::setup_camera)
         // .add_system(player::spawn_player)
         // .add_system(player::player_control_system)
@@ -51,24 +80,6 @@ impl Plugin for GamePlugin {
     }
 }
 
-fn test_setup(
-    mut commands: Commands,
-    // asset_server: Res<AssetServer>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Mesh::from(bevy_shape::Plane { size: 50.0 })),
-        material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
-        transform: Transform::from_xyz(0.0, -10.0, 500.0),
-        ..Default::default()
-    });
-    commands.spawn(PointLightBundle {
-        transform: Transform::from_xyz(4.0, 5.0, -4.0),
-        ..Default::default()
-    });
-}
-
 // fn game_setup(
 //     mut commands: Commands,
 //     asset_server: Res<AssetServer>,
@@ -0,0 +1,462 @@
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::ship::kinematics::DECELERATION_SLIDE;
+
+// Gameplay and menus read actions instead of raw devices, so every control can be rebound
+
+// Axis and trigger travel past which an action counts as pressed
+const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    PitchUp,
+    PitchDown,
+    StrafeLeft,
+    StrafeRight,
+    Fire,
+    Boost,
+    Pause,
+}
+
+impl Action {
+    // Order the actions are listed on the controls page
+    pub const ALL: [Action; 7] = [
+        Action::PitchUp,
+        Action::PitchDown,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Fire,
+        Action::Boost,
+        Action::Pause,
+    ];
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::PitchUp => "Pitch Up",
+            Action::PitchDown => "Pitch Down",
+            Action::StrafeLeft => "Strafe Left",
+            Action::StrafeRight => "Strafe Right",
+            Action::Fire => "Fire",
+            Action::Boost => "Boost",
+            Action::Pause => "Pause",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+// A gamepad control, matched on every connected pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadBinding {
+    Button(GamepadButtonType),
+    // One half of a stick axis, reporting how far it is pushed that way
+    Axis(GamepadAxisType, AxisDirection),
+}
+
+impl fmt::Display for GamepadBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GamepadBinding::Button(button) => write!(f, "{:?}", button),
+            GamepadBinding::Axis(axis, AxisDirection::Positive) => write!(f, "{:?}+", axis),
+            GamepadBinding::Axis(axis, AxisDirection::Negative) => write!(f, "{:?}-", axis),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionBindings {
+    pub keys: Vec<KeyCode>,
+    pub gamepad: Vec<GamepadBinding>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingDevice {
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub bindings: HashMap<Action, ActionBindings>,
+}
+
+impl Default for InputBindings {
+    fn default() -> InputBindings {
+        use AxisDirection::*;
+        use GamepadAxisType::*;
+        use GamepadBinding::*;
+
+        let bind = |keys: &[KeyCode], gamepad: &[GamepadBinding]| ActionBindings {
+            keys: keys.to_vec(),
+            gamepad: gamepad.to_vec(),
+        };
+        // Pushing forward pitches down, flight-sim style
+        let bindings = [
+            (
+                Action::PitchUp,
+                bind(
+                    &[KeyCode::S, KeyCode::Down],
+                    &[
+                        Axis(LeftStickY, Negative),
+                        Button(GamepadButtonType::DPadDown),
+                    ],
+                ),
+            ),
+            (
+                Action::PitchDown,
+                bind(
+                    &[KeyCode::W, KeyCode::Up],
+                    &[
+                        Axis(LeftStickY, Positive),
+                        Button(GamepadButtonType::DPadUp),
+                    ],
+                ),
+            ),
+            (
+                Action::StrafeLeft,
+                bind(
+                    &[KeyCode::A, KeyCode::Left],
+                    &[
+                        Axis(LeftStickX, Negative),
+                        Button(GamepadButtonType::DPadLeft),
+                    ],
+                ),
+            ),
+            (
+                Action::StrafeRight,
+                bind(
+                    &[KeyCode::D, KeyCode::Right],
+                    &[
+                        Axis(LeftStickX, Positive),
+                        Button(GamepadButtonType::DPadRight),
+                    ],
+                ),
+            ),
+            (
+                Action::Fire,
+                bind(
+                    &[KeyCode::Space],
+                    &[
+                        Button(GamepadButtonType::South),
+                        Button(GamepadButtonType::RightTrigger2),
+                    ],
+                ),
+            ),
+            (
+                Action::Boost,
+                bind(
+                    &[KeyCode::LShift],
+                    &[
+                        Button(GamepadButtonType::West),
+                        Button(GamepadButtonType::LeftTrigger2),
+                    ],
+                ),
+            ),
+            (
+                Action::Pause,
+                bind(&[KeyCode::Escape], &[Button(GamepadButtonType::Start)]),
+            ),
+        ];
+        InputBindings {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> Option<&ActionBindings> {
+        self.bindings.get(&action)
+    }
+
+    // Replaces the action's bindings for one device, taking the control away from any other
+    // action so a single key never drives two actions
+    pub fn rebind_key(&mut self, action: Action, key: KeyCode) {
+        for bindings in self.bindings.values_mut() {
+            bindings.keys.retain(|bound| *bound != key);
+        }
+        self.bindings.entry(action).or_default().keys = vec![key];
+    }
+
+    pub fn rebind_gamepad(&mut self, action: Action, binding: GamepadBinding) {
+        for bindings in self.bindings.values_mut() {
+            bindings.gamepad.retain(|bound| *bound != binding);
+        }
+        self.bindings.entry(action).or_default().gamepad = vec![binding];
+    }
+
+    // Label for the first binding of a device, as shown on the controls page
+    pub fn describe(&self, action: Action, device: BindingDevice) -> String {
+        let bindings = self.get(action);
+        let label = match device {
+            BindingDevice::Keyboard => bindings
+                .and_then(|bindings| bindings.keys.first())
+                .map(|key| format!("{:?}", key)),
+            BindingDevice::Gamepad => bindings
+                .and_then(|bindings| bindings.gamepad.first())
+                .map(|binding| binding.to_string()),
+        };
+        label.unwrap_or_else(|| "-".to_string())
+    }
+}
+
+// How the ship responds to the controls, chosen on the flight settings page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Handling {
+    // Quick to reach speed and stops almost as soon as the input is released
+    #[default]
+    Arcade,
+    // Slower to build speed and keeps drifting after the input is released
+    Inertial,
+}
+
+impl Handling {
+    // Multiplier on the hull's accelerations
+    pub fn acceleration_scale(self) -> f32 {
+        match self {
+            Handling::Arcade => 1.0,
+            Handling::Inertial => 0.6,
+        }
+    }
+
+    // Drift decay rate per second once an axis has no input
+    pub fn damping(self) -> f32 {
+        match self {
+            Handling::Arcade => DECELERATION_SLIDE,
+            Handling::Inertial => 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+pub struct ControlSettings {
+    // Off keeps the flight-sim layout where pushing forward pitches down
+    pub invert_y: bool,
+    pub sensitivity: f32,
+    // Stick travel ignored around the centre, as a fraction of full travel
+    pub dead_zone: f32,
+    pub handling: Handling,
+}
+
+impl Default for ControlSettings {
+    fn default() -> ControlSettings {
+        ControlSettings {
+            invert_y: false,
+            sensitivity: 1.0,
+            dead_zone: 0.1,
+            handling: Handling::Arcade,
+        }
+    }
+}
+
+impl ControlSettings {
+    // Turns a raw -1..=1 steering axis into thrust: dead zone removed, rescaled so full travel
+    // still reaches 1, then scaled by sensitivity
+    pub fn shape_axis(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+        let live = (magnitude - self.dead_zone) / (1.0 - self.dead_zone).max(f32::EPSILON);
+        (value.signum() * live * self.sensitivity).clamp(-1.0, 1.0)
+    }
+}
+
+// Per-frame state of every action, in 0..=1 for analog controls
+#[derive(Debug, Default, Resource)]
+pub struct ActionState {
+    values: HashMap<Action, f32>,
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    // Stops later systems this frame from reacting to the same press
+    pub fn consume(&mut self, action: Action) {
+        self.just_pressed.remove(&action);
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.just_pressed.clear();
+    }
+}
+
+// Set by the controls page while it waits for the next key or pad input to bind
+#[derive(Debug, Default, Resource)]
+pub struct PendingRebind(pub Option<(Action, BindingDevice)>);
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<ControlSettings>()
+            .init_resource::<PendingRebind>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                update_action_state.label(ActionSystem).after(InputSystem),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, capture_rebind.after(ActionSystem));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct ActionSystem;
+
+fn update_action_state(
+    bindings: Res<InputBindings>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut state: ResMut<ActionState>,
+) {
+    let was_pressed = std::mem::take(&mut state.pressed);
+    state.clear();
+
+    for (action, action_bindings) in &bindings.bindings {
+        let mut value: f32 = if keys.any_pressed(action_bindings.keys.iter().copied()) {
+            1.0
+        } else {
+            0.0
+        };
+        for gamepad in gamepads.iter() {
+            for binding in &action_bindings.gamepad {
+                value = value.max(gamepad_value(gamepad, *binding, &buttons, &axes));
+            }
+        }
+
+        if value > 0.0 {
+            state.values.insert(*action, value.min(1.0));
+        }
+        if value >= PRESS_THRESHOLD {
+            state.pressed.insert(*action);
+            if !was_pressed.contains(action) {
+                state.just_pressed.insert(*action);
+            }
+        }
+    }
+}
+
+fn gamepad_value(
+    gamepad: Gamepad,
+    binding: GamepadBinding,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> f32 {
+    match binding {
+        GamepadBinding::Button(button_type) => {
+            if buttons.pressed(GamepadButton::new(gamepad, button_type)) {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        GamepadBinding::Axis(axis_type, direction) => {
+            let value = axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default();
+            match direction {
+                AxisDirection::Positive => value.max(0.0),
+                AxisDirection::Negative => (-value).max(0.0),
+            }
+        }
+    }
+}
+
+// While a rebind is pending every action is swallowed, and the next input becomes the binding.
+// Escape cancels a keyboard rebind.
+fn capture_rebind(
+    mut pending: ResMut<PendingRebind>,
+    mut bindings: ResMut<InputBindings>,
+    mut state: ResMut<ActionState>,
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    let Some((action, device)) = pending.0 else {
+        return;
+    };
+    state.clear();
+    state.pressed.clear();
+
+    match device {
+        BindingDevice::Keyboard => {
+            if let Some(key) = keys.get_just_pressed().next().copied() {
+                // Used up here, so the menus do not navigate or go back on the same press
+                keys.clear_just_pressed(key);
+                if key != KeyCode::Escape {
+                    bindings.rebind_key(action, key);
+                    // The key is still held, which must not count as a fresh press next frame
+                    state.pressed.insert(action);
+                }
+                pending.0 = None;
+            }
+        }
+        BindingDevice::Gamepad => {
+            if keys.just_pressed(KeyCode::Escape) {
+                keys.clear_just_pressed(KeyCode::Escape);
+                pending.0 = None;
+                return;
+            }
+            let button = buttons.get_just_pressed().next().copied();
+            if let Some(button) = button {
+                buttons.clear_just_pressed(button);
+            }
+            let button = button.map(|button| GamepadBinding::Button(button.button_type));
+            let axis = gamepads.iter().find_map(|gamepad| {
+                [
+                    GamepadAxisType::LeftStickX,
+                    GamepadAxisType::LeftStickY,
+                    GamepadAxisType::RightStickX,
+                    GamepadAxisType::RightStickY,
+                ]
+                .into_iter()
+                .find_map(|axis_type| {
+                    let value = axes
+                        .get(GamepadAxis::new(gamepad, axis_type))
+                        .unwrap_or_default();
+                    if value >= PRESS_THRESHOLD {
+                        Some(GamepadBinding::Axis(axis_type, AxisDirection::Positive))
+                    } else if value <= -PRESS_THRESHOLD {
+                        Some(GamepadBinding::Axis(axis_type, AxisDirection::Negative))
+                    } else {
+                        None
+                    }
+                })
+            });
+            if let Some(binding) = button.or(axis) {
+                bindings.rebind_gamepad(action, binding);
+                state.pressed.insert(action);
+                pending.0 = None;
+            }
+        }
+    }
+}
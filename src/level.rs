@@ -0,0 +1,644 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    core_pipeline::clear_color::ClearColorConfig,
+    ecs::system::EntityCommands,
+    pbr::NotShadowCaster,
+    prelude::shape as bevy_shape,
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    camera::{CameraTracker, CAMERA_OFFSET},
+    catalog::ShipCatalog,
+    collision::{self, Ground, Obstacle, Trigger, TriggerEntered},
+    enemy::Enemy,
+    game::OnGameScreen,
+    menu::DisplayQuality,
+    player::Player,
+    quality::{DetailLevel, QualityProfile},
+    rail::{Oncoming, RailDefinition, RailPath, RailPosition},
+    ship::{
+        self,
+        health::{ContactDamage, Hull, HullSettings, Shield, ShieldSettings},
+    },
+    simulation::{self, SimulatedTransform, SimulationStage},
+    theme::hex_color,
+    weapon::WeaponDamage,
+    GameState,
+};
+
+// Every level is a <name>.level.ron under assets/levels
+pub const LEVEL_EXTENSION: &str = "level.ron";
+pub const LEVELS_DIR: &str = "levels";
+const DEFAULT_LEVEL: &str = "training";
+// Gap kept between the far side of the sky and the camera's far plane
+const SKYBOX_MARGIN: f32 = 10.0;
+// Terrain is a slab this thick, its top face at the terrain's height
+const TERRAIN_THICKNESS: f32 = 1.0;
+
+// Name of the level played next, the file stem under assets/levels
+#[derive(Debug, Clone, PartialEq, Eq, Resource)]
+pub struct SelectedLevel(pub String);
+
+impl Default for SelectedLevel {
+    fn default() -> SelectedLevel {
+        SelectedLevel(DEFAULT_LEVEL.to_string())
+    }
+}
+
+impl SelectedLevel {
+    pub fn path(&self) -> String {
+        format!("{}/{}.{}", LEVELS_DIR, self.0, LEVEL_EXTENSION)
+    }
+}
+
+// Everything placed in a level. Only `name` is required, a level without a rail flies
+// straight down +z.
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "5d8a1f3e-2c47-4b9e-a6d0-7e3b9c1f4a62"]
+pub struct LevelAsset {
+    pub name: String,
+    #[serde(default)]
+    pub rail: Option<RailDefinition>,
+    #[serde(default)]
+    pub terrain: Vec<TerrainDefinition>,
+    #[serde(default)]
+    pub scenery: Vec<SceneryDefinition>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleDefinition>,
+    #[serde(default)]
+    pub targets: Vec<TargetDefinition>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerDefinition>,
+    #[serde(default)]
+    pub lights: Vec<LightDefinition>,
+    #[serde(default)]
+    pub skybox: Option<SkyboxDefinition>,
+}
+
+// Collision shapes, also drawn as plain meshes for props without a model
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum PropShape {
+    Box { size: Vec3 },
+    Ball { radius: f32 },
+    // Upright, `height` is the length of the straight part between the two caps
+    Capsule { radius: f32, height: f32 },
+}
+
+impl PropShape {
+    fn mesh(&self) -> Mesh {
+        match *self {
+            PropShape::Box { size } => bevy_shape::Box::new(size.x, size.y, size.z).into(),
+            PropShape::Ball { radius } => bevy_shape::UVSphere {
+                radius,
+                sectors: 32,
+                stacks: 16,
+            }
+            .into(),
+            PropShape::Capsule { radius, height } => bevy_shape::Capsule {
+                radius,
+                depth: height,
+                ..default()
+            }
+            .into(),
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        match *self {
+            PropShape::Box { size } => Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0),
+            PropShape::Ball { radius } => Collider::ball(radius),
+            PropShape::Capsule { radius, height } => Collider::capsule_y(height / 2.0, radius),
+        }
+    }
+}
+
+fn default_prop_color() -> Color {
+    Color::GRAY
+}
+
+// Anything placed in the level: buildings, rocks, targets
+#[derive(Debug, Clone, Deserialize)]
+pub struct PropDefinition {
+    pub shape: PropShape,
+    pub position: Vec3,
+    // Euler angles in degrees, applied in XYZ order
+    #[serde(default)]
+    pub rotation: Vec3,
+    // Scene drawn in place of the plain shape, which still sets the collider
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default = "default_prop_color", deserialize_with = "hex_color")]
+    pub color: Color,
+}
+
+impl PropDefinition {
+    fn transform(&self) -> Transform {
+        let rotation = self.rotation * std::f32::consts::PI / 180.0;
+        Transform::from_translation(self.position).with_rotation(Quat::from_euler(
+            EulerRot::XYZ,
+            rotation.x,
+            rotation.y,
+            rotation.z,
+        ))
+    }
+}
+
+// Flat ground, `position` is the middle of its top face
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerrainDefinition {
+    pub position: Vec3,
+    // Width along x and length along z
+    pub size: Vec2,
+    #[serde(default = "default_prop_color", deserialize_with = "hex_color")]
+    pub color: Color,
+    #[serde(default)]
+    pub damage: WeaponDamage,
+}
+
+fn lowest_detail() -> DisplayQuality {
+    DisplayQuality::Low
+}
+
+// Decoration ships fly straight through, left out below the given quality
+#[derive(Debug, Clone, Deserialize)]
+pub struct SceneryDefinition {
+    pub prop: PropDefinition,
+    #[serde(default = "lowest_detail")]
+    pub detail: DisplayQuality,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObstacleDefinition {
+    pub prop: PropDefinition,
+    // Dealt to ships flying into it
+    #[serde(default)]
+    pub damage: WeaponDamage,
+}
+
+// Shot at for score, removed once its hull is gone
+#[derive(Debug, Clone, Deserialize)]
+pub struct TargetDefinition {
+    pub prop: PropDefinition,
+    #[serde(default)]
+    pub hull: HullSettings,
+    #[serde(default)]
+    pub shield: Option<ShieldSettings>,
+}
+
+// An invisible box that spawns enemies the first time the player flies through it
+#[derive(Debug, Clone, Deserialize)]
+pub struct TriggerDefinition {
+    pub name: String,
+    pub position: Vec3,
+    pub size: Vec3,
+    #[serde(default)]
+    pub spawns: Vec<EnemySpawn>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemySpawn {
+    // Catalog id, the directory name under assets/ships
+    pub ship: String,
+    // In the rail's frame: sideways and up from the rail, then distance along it. Enemies fly
+    // the rail towards the player from there.
+    pub position: Vec3,
+    #[serde(default)]
+    pub skin: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum LightDefinition {
+    Point {
+        position: Vec3,
+        #[serde(deserialize_with = "hex_color")]
+        color: Color,
+        intensity: f32,
+        range: f32,
+        #[serde(default)]
+        shadows: bool,
+    },
+    // Sunlight shining along `direction`
+    Directional {
+        direction: Vec3,
+        #[serde(deserialize_with = "hex_color")]
+        color: Color,
+        illuminance: f32,
+        #[serde(default)]
+        shadows: bool,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SkyboxDefinition {
+    // Clear colour behind everything, also what shows while the texture loads
+    #[serde(deserialize_with = "hex_color")]
+    pub color: Color,
+    // Equirectangular image wrapped around the camera
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+impl LevelAsset {
+    // Scenes and images worth having before the level starts
+    pub fn scenes(&self) -> impl Iterator<Item = &str> {
+        let scenery = self.scenery.iter().map(|scenery| &scenery.prop);
+        let obstacles = self.obstacles.iter().map(|obstacle| &obstacle.prop);
+        let targets = self.targets.iter().map(|target| &target.prop);
+        scenery
+            .chain(obstacles)
+            .chain(targets)
+            .filter_map(|prop| prop.model.as_deref())
+    }
+
+    // Every ship the level's triggers can spawn
+    pub fn enemies(&self) -> impl Iterator<Item = &EnemySpawn> {
+        self.triggers.iter().flat_map(|trigger| &trigger.spawns)
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = &str> {
+        self.skybox
+            .iter()
+            .filter_map(|skybox| skybox.texture.as_deref())
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level: LevelAsset = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[LEVEL_EXTENSION]
+    }
+}
+
+// Ships listed by a trigger, waiting for the player
+#[derive(Component, Debug, Clone)]
+struct EnemySpawner(Vec<EnemySpawn>);
+
+// Keeps the sky centred on the camera so it can never be reached
+#[derive(Component)]
+struct Skybox;
+
+// The sky is centred on the tracker, which the camera is CAMERA_OFFSET away from. Shrinking it
+// by that much keeps all of it within the far plane DisplayQuality sets.
+fn skybox_radius(quality: DisplayQuality) -> f32 {
+    QualityProfile::for_quality(quality).view_distance - CAMERA_OFFSET.length() - SKYBOX_MARGIN
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<LevelAsset>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<SelectedLevel>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(spawn_level))
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_run_criteria(simulation::game_running)
+                    .with_system(spawn_triggered_enemies.after(collision::dispatch_collisions)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Game).with_system(follow_camera_with_skybox),
+            );
+    }
+}
+
+fn spawn_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selected_level: Res<SelectedLevel>,
+    levels: Res<Assets<LevelAsset>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tracker_query: Query<Entity, With<CameraTracker>>,
+    mut camera_query: Query<(&Parent, &mut Camera3d)>,
+    quality: Res<DisplayQuality>,
+) {
+    let Some(level) = levels.get(&asset_server.get_handle(selected_level.path().as_str())) else {
+        // The loading screen waits for the level, so this only happens if it was skipped
+        error!("Level {} is not loaded", selected_level.path());
+        commands.spawn((RailPath::default(), OnGameScreen));
+        return;
+    };
+    info!("Starting level {}", level.name);
+
+    let rail = match &level.rail {
+        Some(definition) => RailPath::new(definition).unwrap_or_else(|err| {
+            error!("Invalid rail in level {}: {}", level.name, err);
+            RailPath::default()
+        }),
+        None => RailPath::default(),
+    };
+    commands.spawn((rail, OnGameScreen));
+
+    for terrain in &level.terrain {
+        let size = Vec3::new(terrain.size.x, TERRAIN_THICKNESS, terrain.size.y);
+        let shape = PropShape::Box { size };
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(shape.mesh()),
+                material: materials.add(terrain.color.into()),
+                transform: Transform::from_translation(
+                    terrain.position - Vec3::Y * TERRAIN_THICKNESS / 2.0,
+                ),
+                ..default()
+            },
+            shape.collider(),
+            Ground,
+            ContactDamage(terrain.damage),
+            OnGameScreen,
+        ));
+    }
+
+    for scenery in &level.scenery {
+        spawn_prop(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            &scenery.prop,
+        )
+        .insert(DetailLevel(scenery.detail));
+    }
+
+    for obstacle in &level.obstacles {
+        spawn_prop(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            &obstacle.prop,
+        )
+        .insert((
+            obstacle.prop.shape.collider(),
+            Obstacle,
+            ContactDamage(obstacle.damage),
+        ));
+    }
+
+    for target in &level.targets {
+        let mut entity = spawn_prop(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            &target.prop,
+        );
+        entity.insert((target.prop.shape.collider(), Hull::new(target.hull)));
+        if let Some(shield) = target.shield {
+            entity.insert(Shield::new(shield));
+        }
+    }
+
+    for trigger in &level.triggers {
+        commands.spawn((
+            TransformBundle::from(Transform::from_translation(trigger.position)),
+            Collider::cuboid(
+                trigger.size.x / 2.0,
+                trigger.size.y / 2.0,
+                trigger.size.z / 2.0,
+            ),
+            Sensor,
+            Trigger(trigger.name.clone()),
+            EnemySpawner(trigger.spawns.clone()),
+            OnGameScreen,
+        ));
+    }
+
+    for light in &level.lights {
+        match *light {
+            LightDefinition::Point {
+                position,
+                color,
+                intensity,
+                range,
+                shadows,
+            } => commands.spawn((
+                PointLightBundle {
+                    point_light: PointLight {
+                        color,
+                        intensity,
+                        range,
+                        shadows_enabled: shadows,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position),
+                    ..default()
+                },
+                OnGameScreen,
+            )),
+            LightDefinition::Directional {
+                direction,
+                color,
+                illuminance,
+                shadows,
+            } => commands.spawn((
+                DirectionalLightBundle {
+                    directional_light: DirectionalLight {
+                        color,
+                        illuminance,
+                        shadows_enabled: shadows,
+                        ..default()
+                    },
+                    transform: Transform::default().looking_at(direction, Vec3::Y),
+                    ..default()
+                },
+                OnGameScreen,
+            )),
+        };
+    }
+
+    // The game camera outlives the level, so it is set back to the default clear colour when
+    // a level has no sky of its own
+    let clear_color = level
+        .skybox
+        .as_ref()
+        .map_or(ClearColorConfig::Default, |skybox| {
+            ClearColorConfig::Custom(skybox.color)
+        });
+    for (parent, mut camera) in &mut camera_query {
+        if tracker_query.contains(parent.get()) {
+            camera.clear_color = clear_color.clone();
+        }
+    }
+    if let Some(texture) = level
+        .skybox
+        .as_ref()
+        .and_then(|skybox| skybox.texture.as_ref())
+    {
+        commands.spawn((
+            PbrBundle {
+                // Unit sphere, scaled to the current view distance
+                mesh: meshes.add(
+                    bevy_shape::UVSphere {
+                        radius: 1.0,
+                        sectors: 32,
+                        stacks: 16,
+                    }
+                    .into(),
+                ),
+                material: materials.add(StandardMaterial {
+                    base_color_texture: Some(asset_server.load(texture.as_str())),
+                    unlit: true,
+                    // Seen from the inside
+                    cull_mode: None,
+                    ..default()
+                }),
+                transform: Transform::from_scale(Vec3::splat(skybox_radius(*quality))),
+                ..default()
+            },
+            NotShadowCaster,
+            Skybox,
+            OnGameScreen,
+        ));
+    }
+}
+
+fn spawn_prop<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    prop: &PropDefinition,
+) -> EntityCommands<'w, 's, 'a> {
+    let transform = prop.transform();
+    let mut entity = match &prop.model {
+        Some(model) => commands.spawn(SceneBundle {
+            scene: asset_server.load(model.as_str()),
+            transform,
+            ..default()
+        }),
+        None => commands.spawn(PbrBundle {
+            mesh: meshes.add(prop.shape.mesh()),
+            material: materials.add(prop.color.into()),
+            transform,
+            ..default()
+        }),
+    };
+    entity.insert(OnGameScreen);
+    entity
+}
+
+// Triggers go off once, for the player only
+fn spawn_triggered_enemies(
+    mut commands: Commands,
+    mut triggers: EventReader<TriggerEntered>,
+    asset_server: Res<AssetServer>,
+    catalog: Res<ShipCatalog>,
+    player_query: Query<(), With<Player>>,
+    spawner_query: Query<(&Trigger, &EnemySpawner)>,
+    rail_query: Query<&RailPath>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Ok(rail) = rail_query.get_single() else {
+        return;
+    };
+    for event in triggers.iter() {
+        if !player_query.contains(event.ship) {
+            continue;
+        }
+        let Ok((Trigger(name), EnemySpawner(spawns))) = spawner_query.get(event.trigger) else {
+            continue;
+        };
+        for spawn in spawns {
+            let Some(definition) = catalog.get(&spawn.ship) else {
+                error!(
+                    "Trigger {} spawns {}, which is missing from the catalog",
+                    name, spawn.ship
+                );
+                continue;
+            };
+            // Placed where move_ship_system would put it, so it doesn't blend in from the origin
+            let frame = rail.frame(spawn.position.z);
+            let transform = Transform::from_translation(frame.place(spawn.position.truncate()))
+                .with_rotation(frame.rotation * Quat::from_rotation_y(std::f32::consts::PI));
+            ship::spawn_ship(
+                &mut commands,
+                &asset_server,
+                &mut materials,
+                definition,
+                &spawn.skin,
+            )
+            .insert((
+                transform,
+                SimulatedTransform::new(transform),
+                RailPosition {
+                    local: spawn.position,
+                },
+                Oncoming,
+                Enemy,
+            ));
+        }
+        commands.entity(event.trigger).despawn_recursive();
+    }
+}
+
+fn follow_camera_with_skybox(
+    quality: Res<DisplayQuality>,
+    tracker_query: Query<&Transform, (With<CameraTracker>, Without<Skybox>)>,
+    mut skybox_query: Query<&mut Transform, With<Skybox>>,
+) {
+    let Ok(tracker) = tracker_query.get_single() else {
+        return;
+    };
+    let radius = skybox_radius(*quality);
+    for mut skybox in &mut skybox_query {
+        skybox.translation = tracker.translation;
+        skybox.scale = Vec3::splat(radius);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenery_is_thinned_out_by_quality() {
+        let source = "(
+            scenery: [
+                (prop: (shape: Ball(radius: 2.0), position: (0.0, 0.0, 0.0))),
+                (prop: (shape: Ball(radius: 2.0), position: (5.0, 0.0, 0.0)), detail: High),
+            ],
+            name: \"Scenery\",
+        )";
+        let level: LevelAsset = ron::from_str(source).unwrap();
+        let [always, high] = [0, 1].map(|index| DetailLevel(level.scenery[index].detail));
+        assert!(always.visible_at(DisplayQuality::Low));
+        assert!(!high.visible_at(DisplayQuality::Medium));
+        assert!(high.visible_at(DisplayQuality::High));
+    }
+
+    #[test]
+    fn skybox_stays_inside_the_far_plane() {
+        for quality in [
+            DisplayQuality::Low,
+            DisplayQuality::Medium,
+            DisplayQuality::High,
+        ] {
+            let radius = skybox_radius(quality);
+            let far = QualityProfile::for_quality(quality).view_distance;
+            assert!(radius > 0.0);
+            // Farthest point of the sphere as seen from the camera
+            assert!(radius + CAMERA_OFFSET.length() < far);
+        }
+    }
+}
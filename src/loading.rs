@@ -0,0 +1,280 @@
+use bevy::{
+    asset::{HandleId, LoadState},
+    gltf::Gltf,
+    prelude::*,
+};
+
+use super::{
+    catalog::{SelectedShip, SelectedSkin, ShipCatalog, ShipDefinition},
+    collider::ShipColliderAsset,
+    despawn_screen,
+    level::{LevelAsset, SelectedLevel},
+    parts::TextButton,
+    theme::{PaletteColor, ThemedText, UiTheme},
+    GameState,
+};
+
+const PROGRESS_BAR_WIDTH: f32 = 400.0;
+
+pub struct LoadingPlugin;
+
+#[derive(Component)]
+struct OnLoadingScreen;
+
+#[derive(Component)]
+struct ProgressBarFill;
+
+#[derive(Component)]
+struct ProgressText;
+
+#[derive(Component)]
+struct FailedLoadsText;
+
+#[derive(Component)]
+struct BackToMenuButton;
+
+// Everything a level needs before gameplay starts. Kept alive while in game so nothing unloads.
+#[derive(Resource, Default)]
+pub struct LoadingAssets {
+    handles: Vec<(HandleUntyped, String)>,
+}
+
+impl LoadingAssets {
+    pub fn load<T: bevy::asset::Asset>(&mut self, asset_server: &AssetServer, path: &str) {
+        if self.handles.iter().any(|(_, loading)| loading == path) {
+            return;
+        }
+        let handle: Handle<T> = asset_server.load(path);
+        self.handles
+            .push((handle.clone_untyped(), path.to_string()));
+    }
+}
+
+// Images used by the materials of the glTF file behind `path`, if it is one and has loaded. A
+// scene reports itself loaded without waiting on these.
+fn gltf_textures(
+    path: &str,
+    asset_server: &AssetServer,
+    gltfs: &Assets<Gltf>,
+    materials: &Assets<StandardMaterial>,
+) -> Vec<HandleId> {
+    let file = path.split('#').next().unwrap_or(path);
+    if !file.ends_with(".gltf") && !file.ends_with(".glb") {
+        return vec![];
+    }
+    let Some(gltf) = gltfs.get(&asset_server.get_handle(file)) else {
+        return vec![];
+    };
+    gltf.materials
+        .iter()
+        .filter_map(|material| materials.get(material))
+        .flat_map(|material| {
+            [
+                &material.base_color_texture,
+                &material.emissive_texture,
+                &material.metallic_roughness_texture,
+                &material.normal_map_texture,
+                &material.occlusion_texture,
+            ]
+        })
+        .flatten()
+        .map(|texture| texture.id)
+        .collect()
+}
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Loading).with_system(loading_setup))
+            .add_system_set(
+                SystemSet::on_update(GameState::Loading)
+                    .with_system(loading_progress)
+                    .with_system(back_to_menu_button),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Loading)
+                    .with_system(despawn_screen::<OnLoadingScreen>),
+            );
+    }
+}
+
+// Everything spawn_ship needs to show a hull and make it solid
+fn load_ship(
+    loading: &mut LoadingAssets,
+    asset_server: &AssetServer,
+    ship: &ShipDefinition,
+    skin: &str,
+) {
+    loading.load::<ShipColliderAsset>(asset_server, &ship.definition_path());
+    match &ship.model {
+        Some(model) => loading.load::<Scene>(asset_server, model),
+        None => loading.load::<Mesh>(asset_server, &ship.collider.mesh),
+    }
+    if let Some(texture) = ship.texture(skin) {
+        loading.load::<Image>(asset_server, &texture.path);
+    }
+}
+
+fn loading_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    catalog: Res<ShipCatalog>,
+    selected_ship: Res<SelectedShip>,
+    selected_skin: Res<SelectedSkin>,
+    selected_level: Res<SelectedLevel>,
+    theme: Res<UiTheme>,
+) {
+    let mut loading = LoadingAssets::default();
+    loading.load::<Font>(&asset_server, &theme.fonts.text);
+    loading.load::<LevelAsset>(&asset_server, &selected_level.path());
+    if let Some(ship) = catalog.get(&selected_ship.0) {
+        load_ship(&mut loading, &asset_server, ship, &selected_skin.0);
+    }
+    commands.insert_resource(loading);
+
+    let text_style = theme.text_style(30.0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Auto),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+                background_color: theme.palette.background.into(),
+                ..default()
+            },
+            PaletteColor::Background,
+            OnLoadingScreen,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                TextBundle::from_section("Loading", text_style.clone()),
+                ThemedText::Body,
+                ProgressText,
+            ));
+            p.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(PROGRESS_BAR_WIDTH), Val::Px(30.0)),
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: theme.palette.normal_button.into(),
+                    ..default()
+                },
+                PaletteColor::NormalButton,
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                            ..default()
+                        },
+                        background_color: theme.palette.text.into(),
+                        ..default()
+                    },
+                    PaletteColor::Text,
+                    ProgressBarFill,
+                ));
+            });
+            p.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        ..text_style.clone()
+                    },
+                ),
+                ThemedText::Body,
+                FailedLoadsText,
+            ));
+        });
+}
+
+fn loading_progress(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+    selected_level: Res<SelectedLevel>,
+    levels: Res<Assets<LevelAsset>>,
+    catalog: Res<ShipCatalog>,
+    gltfs: Res<Assets<Gltf>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut game_state: ResMut<State<GameState>>,
+    mut fill_query: Query<&mut Style, With<ProgressBarFill>>,
+    mut progress_query: Query<&mut Text, (With<ProgressText>, Without<FailedLoadsText>)>,
+    mut failed_query: Query<&mut Text, (With<FailedLoadsText>, Without<ProgressText>)>,
+    back_query: Query<(), With<BackToMenuButton>>,
+    screen_query: Query<Entity, With<OnLoadingScreen>>,
+    theme: Res<UiTheme>,
+) {
+    // What the level places is only known once its file is in, so it joins the list then
+    if let Some(level) = levels.get(&asset_server.get_handle(selected_level.path().as_str())) {
+        for scene in level.scenes() {
+            loading.load::<Scene>(&asset_server, scene);
+        }
+        for image in level.images() {
+            loading.load::<Image>(&asset_server, image);
+        }
+        // Enemies appear mid-flight, they should be drawn and solid straight away
+        for spawn in level.enemies() {
+            if let Some(ship) = catalog.get(&spawn.ship) {
+                load_ship(&mut loading, &asset_server, ship, &spawn.skin);
+            }
+        }
+    }
+
+    let mut loaded = 0;
+    let mut failed = vec![];
+    for (handle, path) in &loading.handles {
+        let textures = gltf_textures(path, &asset_server, &gltfs, &materials);
+        let group = std::iter::once(handle.id).chain(textures);
+        match asset_server.get_group_load_state(group) {
+            LoadState::Loaded => loaded += 1,
+            LoadState::Failed => failed.push(path.as_str()),
+            _ => {}
+        }
+    }
+    let total = loading.handles.len().max(1);
+
+    for mut style in &mut fill_query {
+        style.size.width = Val::Percent(100.0 * loaded as f32 / total as f32);
+    }
+    for mut text in &mut progress_query {
+        text.sections[0].value = format!("Loading {}/{}", loaded, loading.handles.len());
+    }
+
+    if !failed.is_empty() {
+        for mut text in &mut failed_query {
+            text.sections[0].value = format!("Failed to load:\n{}", failed.join("\n"));
+        }
+        // Offer a way out once, the failed loads will not recover on their own
+        if back_query.is_empty() {
+            for screen in &screen_query {
+                commands.entity(screen).with_children(|p| {
+                    TextButton::new("Back")
+                        .size(200.0, 50.0)
+                        .font_size(30.0)
+                        .spawn(p, &theme, BackToMenuButton);
+                });
+            }
+        }
+    } else if loaded == loading.handles.len() {
+        game_state.set(GameState::Game).unwrap();
+    }
+}
+
+fn back_to_menu_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<BackToMenuButton>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &query {
+        if *interaction == Interaction::Clicked {
+            game_state.set(GameState::Menu).unwrap();
+        }
+    }
+}
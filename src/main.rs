@@ -3,43 +3,39 @@ use bevy::prelude::shape as bevy_shape;
 use bevy::prelude::*;
 // use bevy_rapier3d::prelude::*;
 
+mod audio;
 mod camera;
+mod catalog;
+mod collider;
+mod collision;
+mod enemy;
 mod game;
+mod input;
+mod level;
+mod loading;
 mod menu;
-// mod parts;
+mod obj;
+mod parts;
+mod pause;
 mod player;
+mod quality;
+mod rail;
+mod settings;
 mod ship;
+mod simulation;
 mod splash_page;
-
-// pub mod parts;
-
-// Standards
-pub const MENU_BACKGROUND_COLOR: BackgroundColor = bevy::prelude::BackgroundColor(Color::CRIMSON);
-pub const MENU_TEXT_COLOR: Color = Color::LIME_GREEN;
-pub const NORMAL_BUTTON_COLOR: Color = Color::ANTIQUE_WHITE;
-pub const HOVERED_BUTTON_COLOR: Color = Color::CYAN;
-pub const HOVERED_PRESSED_BUTTON_COLOR: Color = Color::AZURE;
-pub const PRESSED_BUTTON_COLOR: Color = Color::CRIMSON;
-pub const MENU_FONT: &str = "fonts/FiraMono-Medium.ttf";
+mod theme;
+mod weapon;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub enum GameState {
     Splash,
     Menu,
+    Loading,
     Game,
     Paused,
 }
 
-// #[derive(Debug, Component, PartialEq, Eq, Clone, Copy, Resource)]
-// enum Setting {
-//     DisplayQuality,
-//     Volume,
-// }
-
-// impl Setting {
-//     fn new(setting: T) -> Setting {}
-// }
-
 fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
     for entity in &to_despawn {
         commands.entity(entity).despawn_recursive();
@@ -67,14 +63,26 @@ fn main() {
     //     .run();
 
     App::new()
-        .add_plugins(DefaultPlugins)
-        .insert_resource(menu::DisplayQuality::Medium)
-        .insert_resource(menu::Volume(7))
+        // Lets the UI theme be edited while the game runs
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            watch_for_changes: true,
+            ..default()
+        }))
+        .add_plugin(settings::SettingsPlugin)
+        .add_plugin(quality::QualityPlugin)
+        .add_plugin(audio::SoundPlugin)
         .add_startup_system(camera::setup_camera)
+        .add_startup_system(catalog::load_ship_catalog)
         .add_state(GameState::Splash)
+        .add_plugin(input::ActionsPlugin)
+        .add_plugin(theme::ThemePlugin)
+        .add_plugin(parts::PartsPlugin)
         .add_plugin(splash_page::SplashPlugin)
         .add_plugin(menu::MainMenuPlugin)
+        .add_plugin(loading::LoadingPlugin)
         .add_plugin(game::GamePlugin)
+        .add_plugin(pause::PausePlugin)
+        .add_plugin(collider::ShipColliderPlugin)
         .run();
 }
 
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use super::MenuButtonAction;
+use crate::{
+    input::{Action, BindingDevice, InputBindings, PendingRebind},
+    parts::{Label, LabelledRow, Panel, TextButton},
+    theme::UiTheme,
+};
+
+#[derive(Component)]
+pub struct OnControlsMenuScreen;
+
+// Binding slot on the controls page, waiting for a new input once clicked
+#[derive(Component)]
+pub struct RebindButton {
+    action: Action,
+    device: BindingDevice,
+}
+
+pub fn controls_menu_setup(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    bindings: Res<InputBindings>,
+) {
+    Panel::column().spawn(&mut commands, &theme, OnControlsMenuScreen, |p| {
+        // Column headings
+        LabelledRow::new("Action")
+            .font_size(22.0)
+            .spawn(p, &theme, |p| {
+                for heading in ["Keyboard", "Gamepad"] {
+                    Label::new(heading)
+                        .font_size(22.0)
+                        .width(220.0)
+                        .margin(5.0)
+                        .spawn(p, &theme, ());
+                }
+            });
+        for action in Action::ALL {
+            LabelledRow::new(action.to_string())
+                .font_size(22.0)
+                .spawn(p, &theme, |p| {
+                    for device in [BindingDevice::Keyboard, BindingDevice::Gamepad] {
+                        TextButton::new(bindings.describe(action, device))
+                            .size(220.0, 40.0)
+                            .margin(5.0)
+                            .font_size(22.0)
+                            .spawn(p, &theme, RebindButton { action, device });
+                    }
+                });
+        }
+        TextButton::new("Back")
+            .size(220.0, 40.0)
+            .margin(5.0)
+            .font_size(22.0)
+            .spawn(p, &theme, MenuButtonAction::BackToSettings);
+    });
+}
+
+pub fn rebind_button(
+    interaction_query: Query<(&Interaction, &RebindButton), (Changed<Interaction>, With<Button>)>,
+    mut pending: ResMut<PendingRebind>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction == Interaction::Clicked && pending.0.is_none() {
+            pending.0 = Some((button.action, button.device));
+        }
+    }
+}
+
+pub fn update_rebind_labels(
+    bindings: Res<InputBindings>,
+    pending: Res<PendingRebind>,
+    button_query: Query<(&RebindButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !bindings.is_changed() && !pending.is_changed() {
+        return;
+    }
+    for (button, children) in &button_query {
+        let label = if pending.0 == Some((button.action, button.device)) {
+            "Press input...".to_string()
+        } else {
+            bindings.describe(button.action, button.device)
+        };
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+// Leaving the page drops a rebind that never received its input
+pub fn cancel_rebind(mut pending: ResMut<PendingRebind>) {
+    pending.0 = None;
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use std::mem;
+
+use super::MenuButtonAction;
+use crate::{
+    input::{ControlSettings, Handling},
+    parts::{LabelledRow, OptionGroup, Panel, SelectedOption, TextButton},
+    theme::UiTheme,
+};
+
+#[derive(Component)]
+pub struct OnFlightSettingsMenuScreen;
+
+// One choice on the flight page. Buttons of the same variant form a group with one selection.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum FlightOption {
+    InvertY(bool),
+    Sensitivity(f32),
+    DeadZone(f32),
+    Handling(Handling),
+}
+
+impl FlightOption {
+    fn is_selected(&self, settings: &ControlSettings) -> bool {
+        match *self {
+            FlightOption::InvertY(invert_y) => settings.invert_y == invert_y,
+            FlightOption::Sensitivity(sensitivity) => settings.sensitivity == sensitivity,
+            FlightOption::DeadZone(dead_zone) => settings.dead_zone == dead_zone,
+            FlightOption::Handling(handling) => settings.handling == handling,
+        }
+    }
+
+    fn apply(&self, settings: &mut ControlSettings) {
+        match *self {
+            FlightOption::InvertY(invert_y) => settings.invert_y = invert_y,
+            FlightOption::Sensitivity(sensitivity) => settings.sensitivity = sensitivity,
+            FlightOption::DeadZone(dead_zone) => settings.dead_zone = dead_zone,
+            FlightOption::Handling(handling) => settings.handling = handling,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            FlightOption::InvertY(true) => "On".to_string(),
+            FlightOption::InvertY(false) => "Off".to_string(),
+            FlightOption::Sensitivity(sensitivity) => format!("{}x", sensitivity),
+            FlightOption::DeadZone(dead_zone) => format!("{}%", (dead_zone * 100.0).round()),
+            FlightOption::Handling(handling) => format!("{:?}", handling),
+        }
+    }
+}
+
+pub fn flight_settings_menu_setup(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    settings: Res<ControlSettings>,
+) {
+    let rows = [
+        (
+            "Invert Y",
+            vec![FlightOption::InvertY(false), FlightOption::InvertY(true)],
+        ),
+        (
+            "Sensitivity",
+            [0.5, 0.75, 1.0, 1.5, 2.0]
+                .into_iter()
+                .map(FlightOption::Sensitivity)
+                .collect(),
+        ),
+        (
+            "Dead Zone",
+            [0.0, 0.1, 0.2, 0.3]
+                .into_iter()
+                .map(FlightOption::DeadZone)
+                .collect(),
+        ),
+        (
+            "Handling",
+            vec![
+                FlightOption::Handling(Handling::Arcade),
+                FlightOption::Handling(Handling::Inertial),
+            ],
+        ),
+    ];
+
+    Panel::column().spawn(&mut commands, &theme, OnFlightSettingsMenuScreen, |p| {
+        for (title, options) in rows {
+            LabelledRow::new(title).spawn(p, &theme, |p| {
+                OptionGroup::new(options.into_iter().map(|option| (option, option.label())))
+                    .button_size(120.0, 50.0)
+                    .font_size(25.0)
+                    .spawn(p, &theme, |option| option.is_selected(&settings));
+            });
+        }
+        TextButton::new("Back")
+            .size(200.0, 50.0)
+            .margin(10.0)
+            .font_size(25.0)
+            .spawn(p, &theme, MenuButtonAction::BackToSettings);
+    });
+}
+
+pub fn flight_option_button(
+    interaction_query: Query<
+        (&Interaction, &FlightOption, Entity),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut selected_query: Query<(Entity, &FlightOption, &mut BackgroundColor), With<SelectedOption>>,
+    mut commands: Commands,
+    mut settings: ResMut<ControlSettings>,
+    theme: Res<UiTheme>,
+) {
+    for (interaction, option, entity) in &interaction_query {
+        if *interaction == Interaction::Clicked && !option.is_selected(&settings) {
+            // Only the previous choice in the same row is deselected
+            for (previous_button, previous_option, mut previous_color) in &mut selected_query {
+                if mem::discriminant(previous_option) == mem::discriminant(option) {
+                    *previous_color = theme.palette.normal_button.into();
+                    commands.entity(previous_button).remove::<SelectedOption>();
+                }
+            }
+            commands.entity(entity).insert(SelectedOption);
+            option.apply(&mut settings);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+};
+
+use super::MenuButtonAction;
+use crate::{
+    catalog::{SelectedShip, SelectedSkin, ShipCatalog},
+    parts::{Label, LabelledRow, Panel, SelectedOption, TextButton},
+    ship::insert_ship_model,
+    theme::UiTheme,
+};
+
+// The preview is staged far below the play area so the gameplay camera never sees it
+const PREVIEW_ORIGIN: Vec3 = Vec3::new(0.0, -1000.0, 0.0);
+const PREVIEW_SIZE: u32 = 384;
+const PREVIEW_SPIN_SPEED: f32 = 0.8;
+
+#[derive(Component)]
+pub struct OnHangarScreen;
+
+// Hull button in the ship list, holding the catalog id it selects
+#[derive(Component)]
+pub struct HangarShipButton(String);
+
+// Colour button below the preview, holding the texture variant name it selects
+#[derive(Component)]
+pub struct HangarSkinButton(String);
+
+// Holds the skin buttons, rebuilt whenever another hull is picked
+#[derive(Component)]
+pub struct HangarSkinRow;
+
+#[derive(Component)]
+pub struct HangarPreview;
+
+#[derive(Component)]
+pub struct HangarShipLabel;
+
+pub fn hangar_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    catalog: Res<ShipCatalog>,
+    selected_ship: Res<SelectedShip>,
+    selected_skin: Res<SelectedSkin>,
+    theme: Res<UiTheme>,
+) {
+    let size = Extent3d {
+        width: PREVIEW_SIZE,
+        height: PREVIEW_SIZE,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("hangar_preview"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        },
+        ..default()
+    };
+    image.resize(size);
+    let preview_image = images.add(image);
+
+    commands.spawn((
+        Camera3dBundle {
+            camera_3d: Camera3d {
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            camera: Camera {
+                priority: -1,
+                target: RenderTarget::Image(preview_image.clone()),
+                ..default()
+            },
+            transform: Transform::from_translation(PREVIEW_ORIGIN + Vec3::new(0.0, 3.0, -9.0))
+                .looking_at(PREVIEW_ORIGIN, Vec3::Y),
+            ..default()
+        },
+        UiCameraConfig { show_ui: false },
+        OnHangarScreen,
+    ));
+    commands.spawn((
+        PointLightBundle {
+            point_light: PointLight {
+                intensity: 2000.0,
+                range: 40.0,
+                ..default()
+            },
+            transform: Transform::from_translation(PREVIEW_ORIGIN + Vec3::new(4.0, 6.0, -6.0)),
+            ..default()
+        },
+        OnHangarScreen,
+    ));
+    spawn_preview(
+        &mut commands,
+        &asset_server,
+        &mut materials,
+        &catalog,
+        &selected_ship,
+        &selected_skin,
+    );
+
+    Panel::row().spawn(&mut commands, &theme, OnHangarScreen, |p| {
+        // Hull list
+        Panel::column().padding(20.0).spawn_in(p, &theme, (), |p| {
+            for ship in catalog.iter() {
+                TextButton::new(ship.name.clone())
+                    .selected(ship.id == selected_ship.0)
+                    .spawn(p, &theme, HangarShipButton(ship.id.clone()));
+            }
+        });
+        // Preview and launch controls
+        Panel::column().padding(20.0).spawn_in(p, &theme, (), |p| {
+            Label::new(ship_label(&catalog, &selected_ship)).spawn(p, &theme, HangarShipLabel);
+            p.spawn(ImageBundle {
+                style: Style {
+                    size: Size::new(Val::Px(PREVIEW_SIZE as f32), Val::Px(PREVIEW_SIZE as f32)),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                image: UiImage(preview_image),
+                ..default()
+            });
+            LabelledRow::new("Colour")
+                .label_width(100.0)
+                .spawn(p, &theme, |p| {
+                    Panel::row().spawn_in(p, &theme, HangarSkinRow, |p| {
+                        spawn_skin_buttons(p, &theme, &catalog, &selected_ship, &selected_skin);
+                    });
+                });
+            for (action, text) in [
+                (MenuButtonAction::Launch, "Launch"),
+                (MenuButtonAction::BackToMainMenu, "Back"),
+            ] {
+                TextButton::new(text).spawn(p, &theme, action);
+            }
+        });
+    });
+}
+
+fn spawn_skin_buttons(
+    parent: &mut ChildBuilder,
+    theme: &UiTheme,
+    catalog: &ShipCatalog,
+    selected_ship: &SelectedShip,
+    selected_skin: &SelectedSkin,
+) {
+    let skins = catalog
+        .get(&selected_ship.0)
+        .map(|ship| ship.textures.as_slice())
+        .unwrap_or_default();
+    for skin in skins {
+        TextButton::new(skin.name.clone())
+            .size(70.0, 40.0)
+            .margin(theme.spacing.compact)
+            .font_size(theme.sizes.small)
+            .selected(skin.name == selected_skin.0)
+            .spawn(parent, theme, HangarSkinButton(skin.name.clone()));
+    }
+}
+
+fn spawn_preview(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    materials: &mut Assets<StandardMaterial>,
+    catalog: &ShipCatalog,
+    selected_ship: &SelectedShip,
+    selected_skin: &SelectedSkin,
+) {
+    let mut preview = commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(PREVIEW_ORIGIN)),
+        HangarPreview,
+        OnHangarScreen,
+    ));
+    let Some(ship) = catalog.get(&selected_ship.0) else {
+        return;
+    };
+    preview.with_children(|p| {
+        let mut model = p.spawn(TransformBundle::default());
+        insert_ship_model(&mut model, asset_server, materials, ship, &selected_skin.0);
+    });
+}
+
+fn ship_label(catalog: &ShipCatalog, selected_ship: &SelectedShip) -> String {
+    catalog
+        .get(&selected_ship.0)
+        .map(|ship| ship.name.clone())
+        .unwrap_or_default()
+}
+
+pub fn hangar_ship_button(
+    interaction_query: Query<
+        (&Interaction, &HangarShipButton, Entity),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut selected_query: Query<
+        (Entity, &mut BackgroundColor),
+        (With<SelectedOption>, With<HangarShipButton>),
+    >,
+    preview_query: Query<Entity, With<HangarPreview>>,
+    skin_row_query: Query<Entity, With<HangarSkinRow>>,
+    mut label_query: Query<&mut Text, With<HangarShipLabel>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    catalog: Res<ShipCatalog>,
+    mut selected_ship: ResMut<SelectedShip>,
+    mut selected_skin: ResMut<SelectedSkin>,
+    theme: Res<UiTheme>,
+) {
+    for (interaction, ship_button, entity) in &interaction_query {
+        if Interaction::Clicked == *interaction && selected_ship.0 != ship_button.0 {
+            for (previous_button, mut previous_color) in &mut selected_query {
+                *previous_color = theme.palette.normal_button.into();
+                commands.entity(previous_button).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            selected_ship.0 = ship_button.0.clone();
+
+            // The new hull may not come in the colour that was picked for the old one
+            if let Some(ship) = catalog.get(&selected_ship.0) {
+                if ship.texture(&selected_skin.0).is_none() {
+                    if let Some(texture) = ship.default_texture() {
+                        selected_skin.0 = texture.name.clone();
+                    }
+                }
+            }
+            for row in &skin_row_query {
+                commands.entity(row).despawn_descendants();
+                commands.entity(row).with_children(|p| {
+                    spawn_skin_buttons(p, &theme, &catalog, &selected_ship, &selected_skin);
+                });
+            }
+
+            for preview in &preview_query {
+                commands.entity(preview).despawn_recursive();
+            }
+            spawn_preview(
+                &mut commands,
+                &asset_server,
+                &mut materials,
+                &catalog,
+                &selected_ship,
+                &selected_skin,
+            );
+            for mut label in &mut label_query {
+                label.sections[0].value = ship_label(&catalog, &selected_ship);
+            }
+        }
+    }
+}
+
+pub fn hangar_skin_button(
+    interaction_query: Query<
+        (&Interaction, &HangarSkinButton, Entity),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut selected_query: Query<
+        (Entity, &mut BackgroundColor),
+        (With<SelectedOption>, With<HangarSkinButton>),
+    >,
+    preview_query: Query<Entity, With<HangarPreview>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    catalog: Res<ShipCatalog>,
+    selected_ship: Res<SelectedShip>,
+    mut selected_skin: ResMut<SelectedSkin>,
+    theme: Res<UiTheme>,
+) {
+    for (interaction, skin_button, entity) in &interaction_query {
+        if Interaction::Clicked == *interaction && selected_skin.0 != skin_button.0 {
+            for (previous_button, mut previous_color) in &mut selected_query {
+                *previous_color = theme.palette.normal_button.into();
+                commands.entity(previous_button).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            selected_skin.0 = skin_button.0.clone();
+
+            for preview in &preview_query {
+                commands.entity(preview).despawn_recursive();
+            }
+            spawn_preview(
+                &mut commands,
+                &asset_server,
+                &mut materials,
+                &catalog,
+                &selected_ship,
+                &selected_skin,
+            );
+        }
+    }
+}
+
+pub fn spin_preview(time: Res<Time>, mut query: Query<&mut Transform, With<HangarPreview>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(PREVIEW_SPIN_SPEED * time.delta_seconds());
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use std::{fmt::Debug, marker::PhantomData};
+
+use super::{MenuButtonAction, MenuState};
+use crate::{
+    despawn_screen,
+    parts::{self, LabelledRow, OptionGroup, Panel, TextButton},
+    theme::UiTheme,
+};
+
+// A resource picked from a fixed set of values. Each one gets a settings page with a button per
+// value, see `add_setting_page`.
+pub trait SettingOption: Resource + Component + Default + Copy + PartialEq + Debug {
+    // Shown on the settings menu and as the page heading
+    const TITLE: &'static str;
+
+    fn all_values() -> Vec<Self>;
+
+    fn label(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+// Titles of the registered pages, in the order `MenuState::SettingsPage` indexes them
+#[derive(Default, Resource)]
+pub struct SettingPages(pub Vec<&'static str>);
+
+#[derive(Component)]
+pub struct OnSettingPage<T>(PhantomData<T>);
+
+pub trait AddSettingPage {
+    fn add_setting_page<T: SettingOption>(&mut self) -> &mut Self;
+}
+
+impl AddSettingPage for App {
+    fn add_setting_page<T: SettingOption>(&mut self) -> &mut Self {
+        let mut pages = self
+            .world
+            .get_resource_or_insert_with(SettingPages::default);
+        let page = MenuState::SettingsPage(pages.0.len());
+        pages.0.push(T::TITLE);
+
+        self.init_resource::<T>()
+            .add_system_set(SystemSet::on_enter(page).with_system(setting_page_setup::<T>))
+            .add_system_set(SystemSet::on_update(page).with_system(parts::option_button::<T>))
+            .add_system_set(
+                SystemSet::on_exit(page).with_system(despawn_screen::<OnSettingPage<T>>),
+            )
+    }
+}
+
+fn setting_page_setup<T: SettingOption>(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    setting: Res<T>,
+) {
+    let screen = OnSettingPage::<T>(PhantomData);
+
+    Panel::column().spawn(&mut commands, &theme, screen, |p| {
+        LabelledRow::new(T::TITLE)
+            .label_width(300.0)
+            .font_size(35.0)
+            .spawn(p, &theme, |p| {
+                OptionGroup::new(
+                    T::all_values()
+                        .into_iter()
+                        .map(|value| (value, value.label())),
+                )
+                .spawn(p, &theme, |value| *value == *setting);
+            });
+        TextButton::new("Back")
+            .size(200.0, 50.0)
+            .font_size(35.0)
+            .spawn(p, &theme, MenuButtonAction::BackToSettings);
+    });
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use super::{MenuButtonAction, Volume};
+use crate::{
+    audio::{AudioChannels, SoundEffect, MAX_LEVEL},
+    parts::{LabelledRow, Panel, Slider, TextButton},
+    theme::UiTheme,
+};
+
+#[derive(Component)]
+pub struct OnSoundSettingsMenuScreen;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Effects,
+}
+
+impl VolumeChannel {
+    const ALL: [VolumeChannel; 3] = [
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Effects,
+    ];
+
+    fn level(self, volume: &Volume, channels: &AudioChannels) -> u32 {
+        match self {
+            VolumeChannel::Master => volume.0,
+            VolumeChannel::Music => channels.music,
+            VolumeChannel::Effects => channels.effects,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "Master",
+            VolumeChannel::Music => "Music",
+            VolumeChannel::Effects => "Effects",
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct VolumeSlider(VolumeChannel);
+
+pub fn sound_settings_menu_setup(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    volume: Res<Volume>,
+    channels: Res<AudioChannels>,
+) {
+    Panel::column().spawn(&mut commands, &theme, OnSoundSettingsMenuScreen, |p| {
+        for channel in VolumeChannel::ALL {
+            LabelledRow::new(channel.label())
+                .font_size(40.0)
+                .spawn(p, &theme, |p| {
+                    Slider::new(channel.level(&volume, &channels), MAX_LEVEL).spawn(
+                        p,
+                        &theme,
+                        VolumeSlider(channel),
+                    );
+                });
+        }
+        TextButton::new("Back").size(200.0, 65.0).spawn(
+            p,
+            &theme,
+            MenuButtonAction::BackToSettings,
+        );
+    });
+}
+
+// Copies slider moves into the settings, every write is saved to the settings file
+pub fn apply_volume_sliders(
+    slider_query: Query<(&Slider, &VolumeSlider), Changed<Slider>>,
+    mut volume: ResMut<Volume>,
+    mut channels: ResMut<AudioChannels>,
+    mut sounds: EventWriter<SoundEffect>,
+) {
+    for (slider, volume_slider) in &slider_query {
+        let channel = volume_slider.0;
+        if channel.level(&volume, &channels) == slider.value {
+            continue;
+        }
+        match channel {
+            VolumeChannel::Master => volume.0 = slider.value,
+            VolumeChannel::Music => channels.music = slider.value,
+            VolumeChannel::Effects => channels.effects = slider.value,
+        }
+        // The menu music already plays at the new music level, the others get a sample
+        if channel != VolumeChannel::Music {
+            sounds.send(SoundEffect::Click);
+        }
+    }
+}
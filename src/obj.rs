@@ -0,0 +1,333 @@
+use bevy::prelude::{Vec2, Vec3};
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
+
+// Wavefront OBJ reader for collider geometry, also used to draw hulls that have no glTF export.
+// Materials, groups and smoothing are ignored.
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    InvalidNumber { line: usize, token: String },
+    MissingComponent { line: usize, expected: usize },
+    InvalidIndex { line: usize, token: String },
+    IndexOutOfRange { line: usize, index: i64 },
+    DegenerateFace { line: usize },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "io error: {}", err),
+            ObjError::InvalidNumber { line, token } => {
+                write!(f, "line {}: invalid number {:?}", line, token)
+            }
+            ObjError::MissingComponent { line, expected } => {
+                write!(f, "line {}: expected {} components", line, expected)
+            }
+            ObjError::InvalidIndex { line, token } => {
+                write!(f, "line {}: invalid face index {:?}", line, token)
+            }
+            ObjError::IndexOutOfRange { line, index } => {
+                write!(f, "line {}: face index {} is out of range", line, index)
+            }
+            ObjError::DegenerateFace { line } => {
+                write!(f, "line {}: face needs at least 3 vertices", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> ObjError {
+        ObjError::Io(err)
+    }
+}
+
+// One face corner, as zero-based indices into the mesh attribute lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjVertex {
+    pub position: u32,
+    pub texcoord: Option<u32>,
+    pub normal: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ObjMesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub texcoords: Vec<Vec2>,
+    pub triangles: Vec<[ObjVertex; 3]>,
+}
+
+impl ObjMesh {
+    // Triangle indices into `positions`, the layout Rapier colliders expect
+    pub fn position_indices(&self) -> Vec<[u32; 3]> {
+        self.triangles
+            .iter()
+            .map(|[a, b, c]| [a.position, b.position, c.position])
+            .collect()
+    }
+
+    // Every triangle corner written out on its own, for drawing. Corners without a normal take
+    // their face's, and texture v is flipped to the top-down convention textures are stored in.
+    pub fn flat_vertices(&self) -> FlatVertices {
+        let mut vertices = FlatVertices::default();
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|corner| self.positions[corner.position as usize]);
+            let face_normal = (b - a).cross(c - a).normalize_or_zero();
+            for corner in triangle {
+                let normal = corner
+                    .normal
+                    .map_or(face_normal, |index| self.normals[index as usize]);
+                let texcoord = corner
+                    .texcoord
+                    .map_or(Vec2::ZERO, |index| self.texcoords[index as usize]);
+                vertices
+                    .positions
+                    .push(self.positions[corner.position as usize].to_array());
+                vertices.normals.push(normal.to_array());
+                vertices.uvs.push([texcoord.x, 1.0 - texcoord.y]);
+            }
+        }
+        vertices
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FlatVertices {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+}
+
+pub fn parse_obj<R: BufRead>(reader: R) -> Result<ObjMesh, ObjError> {
+    let mut mesh = ObjMesh::default();
+
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let [x, y, z] = parse_floats::<3>(&mut tokens, line_number)?;
+                mesh.positions.push(Vec3::new(x, y, z));
+            }
+            Some("vn") => {
+                let [x, y, z] = parse_floats::<3>(&mut tokens, line_number)?;
+                mesh.normals.push(Vec3::new(x, y, z));
+            }
+            Some("vt") => {
+                let [u, v] = parse_floats::<2>(&mut tokens, line_number)?;
+                mesh.texcoords.push(Vec2::new(u, v));
+            }
+            Some("f") => {
+                let corners = tokens
+                    .map(|token| parse_corner(token, &mesh, line_number))
+                    .collect::<Result<Vec<ObjVertex>, ObjError>>()?;
+                if corners.len() < 3 {
+                    return Err(ObjError::DegenerateFace { line: line_number });
+                }
+                // Fan triangulation, fine for the convex polygons exporters emit
+                for i in 1..corners.len() - 1 {
+                    mesh.triangles
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+fn parse_floats<'a, const N: usize>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<[f32; N], ObjError> {
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        let token = tokens
+            .next()
+            .ok_or(ObjError::MissingComponent { line, expected: N })?;
+        *value = token.parse().map_err(|_| ObjError::InvalidNumber {
+            line,
+            token: token.to_string(),
+        })?;
+    }
+    Ok(values)
+}
+
+// Handles `v`, `v/vt`, `v//vn` and `v/vt/vn` corners with one-based or negative indices
+fn parse_corner(token: &str, mesh: &ObjMesh, line: usize) -> Result<ObjVertex, ObjError> {
+    let mut parts = token.split('/');
+    let position = match parts.next() {
+        Some(part) if !part.is_empty() => resolve_index(part, mesh.positions.len(), line)?,
+        _ => {
+            return Err(ObjError::InvalidIndex {
+                line,
+                token: token.to_string(),
+            })
+        }
+    };
+    let texcoord = match parts.next() {
+        Some(part) if !part.is_empty() => Some(resolve_index(part, mesh.texcoords.len(), line)?),
+        _ => None,
+    };
+    let normal = match parts.next() {
+        Some(part) if !part.is_empty() => Some(resolve_index(part, mesh.normals.len(), line)?),
+        _ => None,
+    };
+    if parts.next().is_some() {
+        return Err(ObjError::InvalidIndex {
+            line,
+            token: token.to_string(),
+        });
+    }
+
+    Ok(ObjVertex {
+        position,
+        texcoord,
+        normal,
+    })
+}
+
+fn resolve_index(token: &str, count: usize, line: usize) -> Result<u32, ObjError> {
+    let index: i64 = token.parse().map_err(|_| ObjError::InvalidIndex {
+        line,
+        token: token.to_string(),
+    })?;
+    // Negative indices count back from the most recently declared element
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => count as i64 + i,
+        _ => return Err(ObjError::IndexOutOfRange { line, index }),
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(ObjError::IndexOutOfRange { line, index });
+    }
+    Ok(resolved as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, io::BufReader, path::PathBuf};
+
+    const HULLS: [&str; 9] = [
+        "Dispatcher",
+        "Executioner",
+        "Imperial",
+        "Insurgent",
+        "Omen",
+        "Pancake",
+        "Spitfire",
+        "Striker",
+        "Zenith",
+    ];
+
+    fn hull_path(ship: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("assets/ships")
+            .join(ship)
+            .join("OBJ")
+            .join(format!("{}.obj", ship))
+    }
+
+    fn load_obj(path: &PathBuf) -> Result<ObjMesh, ObjError> {
+        parse_obj(BufReader::new(File::open(path)?))
+    }
+
+    #[test]
+    fn parses_bundled_hulls() {
+        for ship in HULLS {
+            let mesh = load_obj(&hull_path(ship)).unwrap_or_else(|err| panic!("{}: {}", ship, err));
+            assert!(!mesh.positions.is_empty(), "{} has no positions", ship);
+            assert!(!mesh.normals.is_empty(), "{} has no normals", ship);
+            assert!(!mesh.texcoords.is_empty(), "{} has no texcoords", ship);
+            assert!(!mesh.triangles.is_empty(), "{} has no faces", ship);
+            for corner in mesh.triangles.iter().flatten() {
+                assert!((corner.position as usize) < mesh.positions.len());
+                assert!((corner.texcoord.unwrap() as usize) < mesh.texcoords.len());
+                assert!((corner.normal.unwrap() as usize) < mesh.normals.len());
+            }
+        }
+    }
+
+    #[test]
+    fn hull_normals_are_not_positions() {
+        let mesh = load_obj(&hull_path("Pancake")).unwrap();
+        assert_eq!(mesh.positions[0], Vec3::new(0.000001, -0.781149, -0.411647));
+        assert!(mesh.normals.iter().all(|n| (n.length() - 1.0).abs() < 0.01));
+    }
+
+    #[test]
+    fn triangulates_ngons() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 0 0\nf 1 2 3 4 5\n";
+        let mesh = parse_obj(source.as_bytes()).unwrap();
+        assert_eq!(
+            mesh.position_indices(),
+            vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn resolves_negative_and_partial_indices() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf -3//-1 -2//-1 -1//-1\n";
+        let mesh = parse_obj(source.as_bytes()).unwrap();
+        assert_eq!(mesh.position_indices(), vec![[0, 1, 2]]);
+        assert_eq!(mesh.triangles[0][0].normal, Some(0));
+        assert_eq!(mesh.triangles[0][0].texcoord, None);
+    }
+
+    #[test]
+    fn flattens_corners_for_drawing() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.25 0\nvn 0 1 0\nf 1/1/1 2 3\n";
+        let vertices = parse_obj(source.as_bytes()).unwrap().flat_vertices();
+        assert_eq!(
+            vertices.positions,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert_eq!(
+            vertices.normals,
+            vec![[0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, 1.0]]
+        );
+        assert_eq!(vertices.uvs, vec![[0.25, 1.0], [0.0, 1.0], [0.0, 1.0]]);
+
+        let mesh = load_obj(&hull_path("Pancake")).unwrap();
+        let vertices = mesh.flat_vertices();
+        assert_eq!(vertices.positions.len(), mesh.triangles.len() * 3);
+        assert_eq!(vertices.normals.len(), vertices.positions.len());
+        assert_eq!(vertices.uvs.len(), vertices.positions.len());
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        let source = "# header\nv 0 0 0\nv 1 nope 0\n";
+        match parse_obj(source.as_bytes()) {
+            Err(ObjError::InvalidNumber { line, token }) => {
+                assert_eq!(line, 3);
+                assert_eq!(token, "nope");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+        assert!(matches!(
+            parse_obj(source.as_bytes()),
+            Err(ObjError::IndexOutOfRange { line: 4, index: 4 })
+        ));
+
+        let source = "v 0 0 0\nv 1 0 0\nf 1 2\n";
+        assert!(matches!(
+            parse_obj(source.as_bytes()),
+            Err(ObjError::DegenerateFace { line: 3 })
+        ));
+    }
+}
@@ -1 +1,38 @@
-pub mod custom_button;
+// Widget kit shared by the menu and pause screens. Builders spawn the entities, the systems in
+// `PartsPlugin` give them their behaviour.
+mod button;
+mod layout;
+mod navigation;
+mod option_group;
+mod slider;
+mod toggle;
+
+use bevy::prelude::*;
+
+pub use button::{IconButton, TextButton};
+pub use layout::{Label, LabelledRow, Panel};
+pub use navigation::Focused;
+pub use option_group::{option_button, OptionGroup, SelectedOption};
+pub use slider::Slider;
+pub use toggle::Toggle;
+
+pub struct PartsPlugin;
+
+impl Plugin for PartsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(button::button_colors)
+            .add_system(toggle::toggle_button)
+            .add_system(navigation::focus_on_hover)
+            .add_system(navigation::navigate_focus.after(navigation::focus_on_hover))
+            .add_system(slider::slider_pointer)
+            .add_system(slider::slider_keys)
+            .add_system(
+                slider::update_sliders
+                    .after(slider::slider_pointer)
+                    .after(slider::slider_keys),
+            )
+            // Focus moves are commands, applied at the end of Update
+            .add_system_to_stage(CoreStage::PostUpdate, navigation::focus_colors)
+            .add_system_to_stage(CoreStage::PostUpdate, navigation::theme_colors);
+    }
+}
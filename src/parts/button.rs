@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+
+use super::{Focused, SelectedOption};
+use crate::theme::{Palette, ThemedText, UiTheme};
+
+// Button with a caption and an optional icon pinned to its left edge. Anything not set falls back
+// to the theme.
+pub struct TextButton {
+    label: String,
+    size: Option<(f32, f32)>,
+    margin: Option<f32>,
+    font_size: Option<f32>,
+    icon: Option<Handle<Image>>,
+    selected: bool,
+}
+
+impl TextButton {
+    pub fn new(label: impl Into<String>) -> TextButton {
+        TextButton {
+            label: label.into(),
+            size: None,
+            margin: None,
+            font_size: None,
+            icon: None,
+            selected: false,
+        }
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> TextButton {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn margin(mut self, margin: f32) -> TextButton {
+        self.margin = Some(margin);
+        self
+    }
+
+    pub fn font_size(mut self, font_size: f32) -> TextButton {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    pub fn icon(mut self, icon: Handle<Image>) -> TextButton {
+        self.icon = Some(icon);
+        self
+    }
+
+    // Starts out as the chosen option of its group
+    pub fn selected(mut self, selected: bool) -> TextButton {
+        self.selected = selected;
+        self
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, theme: &UiTheme, bundle: impl Bundle) -> Entity {
+        let text_style = theme.text_style(self.font_size.unwrap_or(theme.sizes.button));
+        let (width, height) = self
+            .size
+            .unwrap_or((theme.sizes.button_width, theme.sizes.button_height));
+        let background_color = if self.selected {
+            theme.palette.pressed_button
+        } else {
+            theme.palette.normal_button
+        };
+        let mut button = parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(width), Val::Px(height)),
+                    margin: UiRect::all(Val::Px(self.margin.unwrap_or(theme.spacing.button))),
+                    // Text sits to the right of an icon rather than over it
+                    justify_content: if self.icon.is_some() {
+                        JustifyContent::FlexEnd
+                    } else {
+                        JustifyContent::Center
+                    },
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: background_color.into(),
+                ..default()
+            },
+            bundle,
+        ));
+        if self.selected {
+            button.insert(SelectedOption);
+        }
+        button.with_children(|p| {
+            if let Some(icon) = self.icon {
+                p.spawn(ImageBundle {
+                    style: icon_style(),
+                    image: UiImage(icon),
+                    ..default()
+                });
+            }
+            p.spawn((
+                TextBundle::from_section(self.label, text_style),
+                ThemedText::Body,
+            ));
+        });
+        button.id()
+    }
+}
+
+// Square button showing only an image
+pub struct IconButton {
+    icon: Handle<Image>,
+    size: Option<f32>,
+    margin: Option<f32>,
+}
+
+impl IconButton {
+    pub fn new(icon: Handle<Image>) -> IconButton {
+        IconButton {
+            icon,
+            size: None,
+            margin: None,
+        }
+    }
+
+    pub fn size(mut self, size: f32) -> IconButton {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn margin(mut self, margin: f32) -> IconButton {
+        self.margin = Some(margin);
+        self
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, theme: &UiTheme, bundle: impl Bundle) -> Entity {
+        let size = self.size.unwrap_or(theme.sizes.button_height);
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(size), Val::Px(size)),
+                        margin: UiRect::all(Val::Px(self.margin.unwrap_or(theme.spacing.compact))),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: theme.palette.normal_button.into(),
+                    ..default()
+                },
+                bundle,
+            ))
+            .with_children(|p| {
+                p.spawn(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(60.0), Val::Auto),
+                        ..default()
+                    },
+                    image: UiImage(self.icon),
+                    ..default()
+                });
+            })
+            .id()
+    }
+}
+
+fn icon_style() -> Style {
+    Style {
+        size: Size::new(Val::Px(30.0), Val::Auto),
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            left: Val::Px(10.0),
+            right: Val::Auto,
+            top: Val::Auto,
+            bottom: Val::Auto,
+        },
+        ..default()
+    }
+}
+
+// Focus from the keyboard or gamepad looks the same as a mouse hover
+pub fn button_color(
+    palette: &Palette,
+    interaction: Interaction,
+    selected: bool,
+    focused: bool,
+) -> Color {
+    let hovered = interaction == Interaction::Hovered || focused;
+    match (interaction, selected, hovered) {
+        (Interaction::Clicked, _, _) => palette.pressed_button,
+        (_, true, true) => palette.hovered_pressed_button,
+        (_, true, false) => palette.pressed_button,
+        (_, false, true) => palette.hovered_button,
+        (_, false, false) => palette.normal_button,
+    }
+}
+
+pub fn button_colors(
+    theme: Res<UiTheme>,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            Option<&SelectedOption>,
+            Option<&Focused>,
+        ),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut color, selected, focused) in &mut interaction_query {
+        *color = button_color(
+            &theme.palette,
+            *interaction,
+            selected.is_some(),
+            focused.is_some(),
+        )
+        .into();
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+
+use crate::theme::{PaletteColor, ThemedText, UiTheme};
+
+// Centred container that every screen is built in
+pub struct Panel {
+    direction: FlexDirection,
+    padding: f32,
+}
+
+impl Panel {
+    pub fn column() -> Panel {
+        Panel {
+            direction: FlexDirection::Column,
+            padding: 0.0,
+        }
+    }
+
+    pub fn row() -> Panel {
+        Panel {
+            direction: FlexDirection::Row,
+            padding: 0.0,
+        }
+    }
+
+    // Lays the children out from the far end
+    pub fn reversed(mut self) -> Panel {
+        self.direction = match self.direction {
+            FlexDirection::Column => FlexDirection::ColumnReverse,
+            FlexDirection::Row => FlexDirection::RowReverse,
+            direction => direction,
+        };
+        self
+    }
+
+    pub fn padding(mut self, padding: f32) -> Panel {
+        self.padding = padding;
+        self
+    }
+
+    fn bundle(&self, theme: &UiTheme) -> NodeBundle {
+        NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Auto),
+                flex_direction: self.direction,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(self.padding)),
+                ..default()
+            },
+            background_color: theme.palette.background.into(),
+            ..default()
+        }
+    }
+
+    pub fn spawn(
+        self,
+        commands: &mut Commands,
+        theme: &UiTheme,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        commands
+            .spawn((self.bundle(theme), PaletteColor::Background, bundle))
+            .with_children(children)
+            .id()
+    }
+
+    pub fn spawn_in(
+        self,
+        parent: &mut ChildBuilder,
+        theme: &UiTheme,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        parent
+            .spawn((self.bundle(theme), PaletteColor::Background, bundle))
+            .with_children(children)
+            .id()
+    }
+}
+
+// Caption in a fixed width column followed by the row's widgets
+pub struct LabelledRow {
+    label: String,
+    label_width: f32,
+    font_size: Option<f32>,
+}
+
+impl LabelledRow {
+    pub fn new(label: impl Into<String>) -> LabelledRow {
+        LabelledRow {
+            label: label.into(),
+            label_width: 200.0,
+            font_size: None,
+        }
+    }
+
+    pub fn label_width(mut self, label_width: f32) -> LabelledRow {
+        self.label_width = label_width;
+        self
+    }
+
+    pub fn font_size(mut self, font_size: f32) -> LabelledRow {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    pub fn spawn(
+        self,
+        parent: &mut ChildBuilder,
+        theme: &UiTheme,
+        children: impl FnOnce(&mut ChildBuilder),
+    ) -> Entity {
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: theme.palette.background.into(),
+                    ..default()
+                },
+                PaletteColor::Background,
+            ))
+            .with_children(|p| {
+                Label::new(self.label)
+                    .font_size(self.font_size.unwrap_or(theme.sizes.text))
+                    .width(self.label_width)
+                    .spawn(p, theme, ());
+                children(p);
+            })
+            .id()
+    }
+}
+
+// Plain text, or a screen title in the theme's title font
+pub struct Label {
+    text: String,
+    title: bool,
+    font_size: Option<f32>,
+    margin: f32,
+    width: Option<f32>,
+}
+
+impl Label {
+    pub fn new(text: impl Into<String>) -> Label {
+        Label {
+            text: text.into(),
+            title: false,
+            font_size: None,
+            margin: 0.0,
+            width: None,
+        }
+    }
+
+    pub fn title(text: impl Into<String>) -> Label {
+        Label {
+            title: true,
+            ..Label::new(text)
+        }
+    }
+
+    pub fn font_size(mut self, font_size: f32) -> Label {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    pub fn margin(mut self, margin: f32) -> Label {
+        self.margin = margin;
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> Label {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, theme: &UiTheme, bundle: impl Bundle) -> Entity {
+        let (text_style, role) = if self.title {
+            let text_style = TextStyle {
+                font: theme.title_font(),
+                font_size: self.font_size.unwrap_or(theme.sizes.title),
+                color: theme.palette.text,
+            };
+            (text_style, ThemedText::Title)
+        } else {
+            let text_style = theme.text_style(self.font_size.unwrap_or(theme.sizes.text));
+            (text_style, ThemedText::Body)
+        };
+        parent
+            .spawn((
+                TextBundle::from_section(self.text, text_style).with_style(Style {
+                    size: Size::new(self.width.map_or(Val::Auto, Val::Px), Val::Auto),
+                    margin: UiRect::all(Val::Px(self.margin)),
+                    ..default()
+                }),
+                role,
+                bundle,
+            ))
+            .id()
+    }
+}
@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+
+use super::{button::button_color, SelectedOption, Slider};
+use crate::{
+    input::PendingRebind,
+    theme::{Palette, UiTheme},
+};
+
+// Button or slider that keyboard and gamepad input acts on. Hovering with the mouse moves it too,
+// so only one control is ever highlighted.
+#[derive(Component)]
+pub struct Focused;
+
+// Stick deflection that counts as a press, and the point where it lets go again
+const STICK_PRESS: f32 = 0.5;
+const STICK_RELEASE: f32 = 0.3;
+// Sideways distance counts this much more than distance along the pressed direction
+const CROSS_AXIS_WEIGHT: f32 = 2.0;
+
+enum NavCommand {
+    // In UI space, y pointing down
+    Move(Vec2),
+    Activate,
+}
+
+fn read_nav_command(
+    keys: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    stick_held: &mut bool,
+) -> Option<NavCommand> {
+    let pad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter])
+        || pad_pressed(GamepadButtonType::South)
+    {
+        return Some(NavCommand::Activate);
+    }
+    for (key, button_type, direction) in [
+        (KeyCode::Up, GamepadButtonType::DPadUp, Vec2::NEG_Y),
+        (KeyCode::Down, GamepadButtonType::DPadDown, Vec2::Y),
+        (KeyCode::Left, GamepadButtonType::DPadLeft, Vec2::NEG_X),
+        (KeyCode::Right, GamepadButtonType::DPadRight, Vec2::X),
+    ] {
+        if keys.just_pressed(key) || pad_pressed(button_type) {
+            return Some(NavCommand::Move(direction));
+        }
+    }
+
+    // The stick steps once per push, it has to come back towards the centre before the next one
+    let stick = gamepads
+        .iter()
+        .map(|gamepad| {
+            let axis = |axis_type| {
+                axes.get(GamepadAxis::new(gamepad, axis_type))
+                    .unwrap_or_default()
+            };
+            Vec2::new(
+                axis(GamepadAxisType::LeftStickX),
+                -axis(GamepadAxisType::LeftStickY),
+            )
+        })
+        .max_by(|a, b| a.length().total_cmp(&b.length()))
+        .unwrap_or_default();
+    if *stick_held {
+        *stick_held = stick.length() > STICK_RELEASE;
+        return None;
+    }
+    if stick.length() < STICK_PRESS {
+        return None;
+    }
+    *stick_held = true;
+    Some(NavCommand::Move(if stick.x.abs() > stick.y.abs() {
+        Vec2::new(stick.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, stick.y.signum())
+    }))
+}
+
+fn move_focus(commands: &mut Commands, from: Option<Entity>, to: Entity) {
+    if from == Some(to) {
+        return;
+    }
+    if let Some(from) = from {
+        commands.entity(from).remove::<Focused>();
+    }
+    commands.entity(to).insert(Focused);
+}
+
+// Arrows, D-pad or the left stick move to the nearest control in that direction, Enter or South
+// presses the focused button
+pub fn navigate_focus(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    pending: Res<PendingRebind>,
+    mut stick_held: Local<bool>,
+    mut pressed: Local<Option<Entity>>,
+    mut target_query: Query<
+        (Entity, &GlobalTransform, &mut Interaction, Option<&Slider>),
+        Or<(With<Button>, With<Slider>)>,
+    >,
+    focused_query: Query<Entity, With<Focused>>,
+    mut commands: Commands,
+) {
+    // A press made last frame is let go again, like a click would be
+    if let Some(entity) = pressed.take() {
+        if let Ok((_, _, mut interaction, _)) = target_query.get_mut(entity) {
+            if *interaction == Interaction::Clicked {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+    // The controls page is waiting for a key to bind, not for navigation
+    if pending.0.is_some() {
+        return;
+    }
+    let command = match read_nav_command(&keys, &gamepads, &buttons, &axes, &mut stick_held) {
+        Some(command) => command,
+        None => return,
+    };
+
+    let focused = focused_query
+        .iter()
+        .next()
+        .filter(|entity| target_query.contains(*entity));
+    let focused = match focused {
+        Some(focused) => focused,
+        // The first input only brings up the highlight, on the top left control
+        None => {
+            let first = target_query
+                .iter()
+                .map(|(entity, transform, _, _)| (entity, transform.translation()))
+                .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+            if let Some((first, _)) = first {
+                move_focus(&mut commands, None, first);
+            }
+            return;
+        }
+    };
+
+    match command {
+        NavCommand::Activate => {
+            if let Ok((entity, _, mut interaction, None)) = target_query.get_mut(focused) {
+                *interaction = Interaction::Clicked;
+                *pressed = Some(entity);
+            }
+        }
+        NavCommand::Move(direction) => {
+            let (origin, on_slider) = match target_query.get(focused) {
+                Ok((_, transform, _, slider)) => {
+                    (transform.translation().truncate(), slider.is_some())
+                }
+                Err(_) => return,
+            };
+            // Left and right adjust a focused slider instead
+            if on_slider && direction.y == 0.0 {
+                return;
+            }
+            let cross = direction.perp();
+            let next = target_query
+                .iter()
+                .filter(|(entity, ..)| *entity != focused)
+                .filter_map(|(entity, transform, _, _)| {
+                    let offset = transform.translation().truncate() - origin;
+                    let along = offset.dot(direction);
+                    (along > 0.0)
+                        .then(|| (entity, along + offset.dot(cross).abs() * CROSS_AXIS_WEIGHT))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            if let Some((next, _)) = next {
+                move_focus(&mut commands, Some(focused), next);
+            }
+        }
+    }
+}
+
+pub fn focus_on_hover(
+    hover_query: Query<
+        (Entity, &Interaction),
+        (Changed<Interaction>, Or<(With<Button>, With<Slider>)>),
+    >,
+    focused_query: Query<Entity, With<Focused>>,
+    mut commands: Commands,
+) {
+    for (entity, interaction) in &hover_query {
+        if *interaction == Interaction::Hovered {
+            move_focus(&mut commands, focused_query.iter().next(), entity);
+        }
+    }
+}
+
+type ControlColorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static mut BackgroundColor,
+        Option<&'static SelectedOption>,
+        Option<&'static Focused>,
+        Option<&'static Slider>,
+    ),
+    Or<(With<Button>, With<Slider>)>,
+>;
+
+// Sliders only show focus, buttons also their interaction and selection
+fn control_color(
+    palette: &Palette,
+    interaction: Interaction,
+    selected: bool,
+    focused: bool,
+    slider: bool,
+) -> Color {
+    match (slider, focused) {
+        (true, true) => palette.hovered_button,
+        (true, false) => palette.normal_button,
+        (false, _) => button_color(palette, interaction, selected, focused),
+    }
+}
+
+// Runs after the focus commands are applied, so both ends of a move are seen
+pub fn focus_colors(
+    theme: Res<UiTheme>,
+    removed: RemovedComponents<Focused>,
+    added_query: Query<Entity, Added<Focused>>,
+    mut color_query: ControlColorQuery,
+) {
+    for entity in removed.iter().chain(added_query.iter()) {
+        if let Ok((interaction, mut color, selected, focused, slider)) = color_query.get_mut(entity)
+        {
+            *color = control_color(
+                &theme.palette,
+                *interaction,
+                selected.is_some(),
+                focused.is_some(),
+                slider.is_some(),
+            )
+            .into();
+        }
+    }
+}
+
+// Controls carry no palette role, their colour depends on their state, so a new theme recolours
+// them from that state
+pub fn theme_colors(theme: Res<UiTheme>, mut color_query: ControlColorQuery) {
+    if !theme.is_changed() {
+        return;
+    }
+    for (interaction, mut color, selected, focused, slider) in &mut color_query {
+        *color = control_color(
+            &theme.palette,
+            *interaction,
+            selected.is_some(),
+            focused.is_some(),
+            slider.is_some(),
+        )
+        .into();
+    }
+}
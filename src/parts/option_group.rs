@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use super::TextButton;
+use crate::theme::UiTheme;
+
+// Marks the chosen button of an option group
+#[derive(Component)]
+pub struct SelectedOption;
+
+// Row of mutually exclusive buttons, each carrying its value as a component
+pub struct OptionGroup<T> {
+    options: Vec<(T, String)>,
+    width: f32,
+    height: f32,
+    font_size: f32,
+}
+
+impl<T: Component + Copy> OptionGroup<T> {
+    pub fn new(options: impl IntoIterator<Item = (T, String)>) -> OptionGroup<T> {
+        OptionGroup {
+            options: options.into_iter().collect(),
+            width: 150.0,
+            height: 65.0,
+            font_size: 35.0,
+        }
+    }
+
+    pub fn button_size(mut self, width: f32, height: f32) -> OptionGroup<T> {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn font_size(mut self, font_size: f32) -> OptionGroup<T> {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn spawn(
+        self,
+        parent: &mut ChildBuilder,
+        theme: &UiTheme,
+        is_selected: impl Fn(&T) -> bool,
+    ) {
+        for (option, label) in self.options {
+            TextButton::new(label)
+                .size(self.width, self.height)
+                .margin(theme.spacing.compact)
+                .font_size(self.font_size)
+                .selected(is_selected(&option))
+                .spawn(parent, theme, option);
+        }
+    }
+}
+
+// Moves the selection of a group whose buttons hold values of the resource `T`
+pub fn option_button<T: Component + PartialEq + Copy + Resource>(
+    interaction_query: Query<(&Interaction, &T, Entity), (Changed<Interaction>, With<Button>)>,
+    mut selected_query: Query<(Entity, &mut BackgroundColor), (With<SelectedOption>, With<T>)>,
+    mut commands: Commands,
+    mut setting: ResMut<T>,
+    theme: Res<UiTheme>,
+) {
+    for (interaction, button_setting, entity) in &interaction_query {
+        if Interaction::Clicked == *interaction && *setting != *button_setting {
+            for (previous_button, mut previous_color) in &mut selected_query {
+                *previous_color = theme.palette.normal_button.into();
+                commands.entity(previous_button).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            *setting = *button_setting;
+        }
+    }
+}
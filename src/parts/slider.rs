@@ -0,0 +1,151 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use super::Focused;
+use crate::theme::{PaletteColor, ThemedText, UiTheme};
+
+// Whole-number slider from 0 to `max`. Screens read `value` back through `Changed<Slider>`.
+// The track is not a `Button`, so dragging does not count as a stream of clicks.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Slider {
+    pub value: u32,
+    pub max: u32,
+}
+
+#[derive(Component)]
+pub struct SliderFill;
+
+#[derive(Component)]
+pub struct SliderValue;
+
+impl Slider {
+    pub fn new(value: u32, max: u32) -> Slider {
+        Slider {
+            value: value.min(max),
+            max,
+        }
+    }
+
+    fn fill_width(&self) -> Val {
+        Val::Percent(self.value as f32 / self.max.max(1) as f32 * 100.0)
+    }
+
+    // Writes only on a real change, so `Changed<Slider>` means the value moved
+    fn set(slider: &mut Mut<Slider>, value: u32) {
+        let value = value.min(slider.max);
+        if slider.value != value {
+            slider.value = value;
+        }
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, theme: &UiTheme, bundle: impl Bundle) -> Entity {
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(300.0), Val::Px(30.0)),
+                        margin: UiRect::new(
+                            Val::Px(10.0),
+                            Val::Px(80.0),
+                            Val::Px(10.0),
+                            Val::Px(10.0),
+                        ),
+                        ..default()
+                    },
+                    background_color: theme.palette.normal_button.into(),
+                    focus_policy: FocusPolicy::Block,
+                    ..default()
+                },
+                Interaction::default(),
+                self,
+                bundle,
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    NodeBundle {
+                        style: Style {
+                            size: Size::new(self.fill_width(), Val::Percent(100.0)),
+                            ..default()
+                        },
+                        background_color: theme.palette.pressed_button.into(),
+                        focus_policy: FocusPolicy::Pass,
+                        ..default()
+                    },
+                    PaletteColor::PressedButton,
+                    SliderFill,
+                ));
+                // Hangs off the right end of the track
+                p.spawn((
+                    TextBundle::from_section(self.value.to_string(), theme.text_style(30.0))
+                        .with_style(Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Percent(105.0),
+                                ..default()
+                            },
+                            ..default()
+                        }),
+                    ThemedText::Body,
+                    SliderValue,
+                ));
+            })
+            .id()
+    }
+}
+
+// Click or drag along a track to set its value
+pub fn slider_pointer(
+    windows: Res<Windows>,
+    mut slider_query: Query<(&Interaction, &Node, &GlobalTransform, &mut Slider)>,
+) {
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| window.cursor_position());
+    for (interaction, node, transform, mut slider) in &mut slider_query {
+        if let (Interaction::Clicked, Some(cursor)) = (interaction, cursor) {
+            let width = node.size().x;
+            let left = transform.translation().x - width / 2.0;
+            let fraction = ((cursor.x - left) / width).clamp(0.0, 1.0);
+            Slider::set(&mut slider, (fraction * slider.max as f32).round() as u32);
+        }
+    }
+}
+
+// Left/Right step the focused slider, moving between controls is up to the navigation
+pub fn slider_keys(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut slider_query: Query<&mut Slider, With<Focused>>,
+) {
+    let pad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    for mut slider in &mut slider_query {
+        let value = slider.value;
+        if keys.just_pressed(KeyCode::Left) || pad_pressed(GamepadButtonType::DPadLeft) {
+            Slider::set(&mut slider, value.saturating_sub(1));
+        }
+        if keys.just_pressed(KeyCode::Right) || pad_pressed(GamepadButtonType::DPadRight) {
+            Slider::set(&mut slider, value + 1);
+        }
+    }
+}
+
+pub fn update_sliders(
+    slider_query: Query<&Slider, Changed<Slider>>,
+    mut fill_query: Query<(&Parent, &mut Style), With<SliderFill>>,
+    mut value_query: Query<(&Parent, &mut Text), With<SliderValue>>,
+) {
+    for (parent, mut style) in &mut fill_query {
+        if let Ok(slider) = slider_query.get(parent.get()) {
+            style.size.width = slider.fill_width();
+        }
+    }
+    for (parent, mut text) in &mut value_query {
+        if let Ok(slider) = slider_query.get(parent.get()) {
+            text.sections[0].value = slider.value.to_string();
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use super::TextButton;
+use crate::theme::UiTheme;
+
+// On/off button. Screens read `on` back through `Changed<Toggle>`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Toggle {
+    pub on: bool,
+}
+
+impl Toggle {
+    pub fn new(on: bool) -> Toggle {
+        Toggle { on }
+    }
+
+    fn label(&self) -> &'static str {
+        if self.on {
+            "On"
+        } else {
+            "Off"
+        }
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, theme: &UiTheme, bundle: impl Bundle) -> Entity {
+        TextButton::new(self.label())
+            .size(120.0, 50.0)
+            .margin(theme.spacing.compact)
+            .font_size(theme.sizes.text)
+            .spawn(parent, theme, (self, bundle))
+    }
+}
+
+pub fn toggle_button(
+    mut toggle_query: Query<(&Interaction, &mut Toggle, &Children), Changed<Interaction>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, mut toggle, children) in &mut toggle_query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        toggle.on = !toggle.on;
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = toggle.label().to_string();
+            }
+        }
+    }
+}
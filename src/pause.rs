@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::RapierConfiguration;
+
+use super::{
+    despawn_screen,
+    input::{Action, ActionState},
+    menu::MenuState,
+    parts::{Label, Panel, TextButton},
+    theme::UiTheme,
+    GameState,
+};
+
+pub struct PausePlugin;
+
+#[derive(Component)]
+struct OnPauseScreen;
+
+#[derive(Component)]
+enum PauseButtonAction {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        // Paused is pushed on top of Game, so the game's own update systems stop on their own
+        app.add_system_set(SystemSet::on_update(GameState::Game).with_system(pause_game))
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(pause_physics))
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(pause_menu_setup)
+                    .with_system(pause_action)
+                    .with_system(pause_input),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(resume_physics)
+                    .with_system(despawn_screen::<OnPauseScreen>),
+            );
+    }
+}
+
+// Lives outside the fixed-rate simulation so a single press is never missed or doubled
+fn pause_game(mut actions: ResMut<ActionState>, mut game_state: ResMut<State<GameState>>) {
+    if actions.just_pressed(Action::Pause) {
+        game_state.push(GameState::Paused).unwrap();
+        // Stop the pause overlay from seeing the same press and resuming straight away
+        actions.consume(Action::Pause);
+    }
+}
+
+fn pause_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
+
+// Shows the overlay whenever no settings page is open on top of it
+fn pause_menu_setup(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    menu_state: Res<State<MenuState>>,
+    screen_query: Query<(), With<OnPauseScreen>>,
+) {
+    if *menu_state.current() != MenuState::Disabled || !screen_query.is_empty() {
+        return;
+    }
+
+    Panel::column()
+        .padding(20.0)
+        .spawn(&mut commands, &theme, OnPauseScreen, |p| {
+            Label::title("Paused").spawn(p, &theme, ());
+            for (action, text) in [
+                (PauseButtonAction::Resume, "Resume"),
+                (PauseButtonAction::Restart, "Restart Level"),
+                (PauseButtonAction::Settings, "Settings"),
+                (PauseButtonAction::QuitToMenu, "Quit to Main Menu"),
+            ] {
+                TextButton::new(text)
+                    .size(300.0, 65.0)
+                    .margin(15.0)
+                    .font_size(30.0)
+                    .spawn(p, &theme, action);
+            }
+        });
+}
+
+fn pause_action(
+    query: Query<(&Interaction, &PauseButtonAction), (Changed<Interaction>, With<Button>)>,
+    screen_query: Query<Entity, With<OnPauseScreen>>,
+    mut commands: Commands,
+    mut menu_state: ResMut<State<MenuState>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for (interaction, action) in &query {
+        if *interaction == Interaction::Clicked {
+            match action {
+                PauseButtonAction::Resume => game_state.pop().unwrap(),
+                // Leaving through Loading tears the level down and builds it again from scratch
+                PauseButtonAction::Restart => game_state.replace(GameState::Loading).unwrap(),
+                PauseButtonAction::Settings => {
+                    for screen in &screen_query {
+                        commands.entity(screen).despawn_recursive();
+                    }
+                    menu_state.set(MenuState::Settings).unwrap();
+                }
+                PauseButtonAction::QuitToMenu => game_state.replace(GameState::Menu).unwrap(),
+            }
+        }
+    }
+}
+
+// Pause backs out of an open settings page first, then resumes the game
+fn pause_input(
+    mut actions: ResMut<ActionState>,
+    mut menu_state: ResMut<State<MenuState>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    if *menu_state.current() != MenuState::Disabled {
+        menu_state.set(MenuState::Disabled).unwrap();
+    } else {
+        game_state.pop().unwrap();
+    }
+    actions.consume(Action::Pause);
+}
@@ -0,0 +1,164 @@
+use bevy::{
+    pbr::{DirectionalLightShadowMap, PointLightShadowMap},
+    prelude::*,
+};
+
+use crate::menu::DisplayQuality;
+
+// Rendering budget for one DisplayQuality setting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityProfile {
+    // wgpu only supports 1 or 4 samples
+    pub msaa_samples: u32,
+    pub shadows: bool,
+    pub shadow_map_size: usize,
+    // Far clip of the gameplay camera. Bevy 0.9 has no distance fog, so this is the only
+    // distance cue until the engine is upgraded.
+    pub view_distance: f32,
+}
+
+impl QualityProfile {
+    pub fn for_quality(quality: DisplayQuality) -> QualityProfile {
+        match quality {
+            DisplayQuality::Low => QualityProfile {
+                msaa_samples: 1,
+                shadows: false,
+                shadow_map_size: 512,
+                view_distance: 400.0,
+            },
+            DisplayQuality::Medium => QualityProfile {
+                msaa_samples: 4,
+                shadows: true,
+                shadow_map_size: 1024,
+                view_distance: 700.0,
+            },
+            DisplayQuality::High => QualityProfile {
+                msaa_samples: 4,
+                shadows: true,
+                shadow_map_size: 4096,
+                view_distance: 1000.0,
+            },
+        }
+    }
+}
+
+// Whether a light was spawned with shadows, recorded the first time quality is applied to it.
+// Quality can only turn shadows off, never on for a light that was made without them.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ShadowsAuthored(pub bool);
+
+// Decorative entity only shown from the given quality upwards, thinning out level detail on
+// lower settings
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DetailLevel(pub DisplayQuality);
+
+impl DetailLevel {
+    pub fn visible_at(&self, quality: DisplayQuality) -> bool {
+        quality >= self.0
+    }
+}
+
+pub struct QualityPlugin;
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_render_quality)
+            .add_system(apply_light_quality)
+            .add_system(apply_view_distance)
+            .add_system(apply_detail_level);
+    }
+}
+
+fn apply_render_quality(
+    quality: Res<DisplayQuality>,
+    mut msaa: ResMut<Msaa>,
+    mut point_shadow_map: ResMut<PointLightShadowMap>,
+    mut directional_shadow_map: ResMut<DirectionalLightShadowMap>,
+) {
+    if !quality.is_changed() {
+        return;
+    }
+    let profile = QualityProfile::for_quality(*quality);
+    info!("Applying {:?} display quality", *quality);
+    msaa.samples = profile.msaa_samples;
+    point_shadow_map.size = profile.shadow_map_size;
+    directional_shadow_map.size = profile.shadow_map_size;
+}
+
+// Lights spawned later, e.g. by a level, pick up the current setting as they appear
+fn apply_light_quality(
+    mut commands: Commands,
+    quality: Res<DisplayQuality>,
+    mut point_query: Query<(
+        Entity,
+        &mut PointLight,
+        Option<&ShadowsAuthored>,
+        ChangeTrackers<PointLight>,
+    )>,
+    mut spot_query: Query<(
+        Entity,
+        &mut SpotLight,
+        Option<&ShadowsAuthored>,
+        ChangeTrackers<SpotLight>,
+    )>,
+    mut directional_query: Query<(
+        Entity,
+        &mut DirectionalLight,
+        Option<&ShadowsAuthored>,
+        ChangeTrackers<DirectionalLight>,
+    )>,
+) {
+    let shadows = QualityProfile::for_quality(*quality).shadows;
+    let changed = quality.is_changed();
+    let mut cap = |entity: Entity, enabled: &mut bool, authored: Option<&ShadowsAuthored>| {
+        let authored = match authored {
+            Some(authored) => authored.0,
+            None => {
+                commands.entity(entity).insert(ShadowsAuthored(*enabled));
+                *enabled
+            }
+        };
+        *enabled = authored && shadows;
+    };
+    for (entity, mut light, authored, tracker) in &mut point_query {
+        if changed || tracker.is_added() {
+            cap(entity, &mut light.shadows_enabled, authored);
+        }
+    }
+    for (entity, mut light, authored, tracker) in &mut spot_query {
+        if changed || tracker.is_added() {
+            cap(entity, &mut light.shadows_enabled, authored);
+        }
+    }
+    for (entity, mut light, authored, tracker) in &mut directional_query {
+        if changed || tracker.is_added() {
+            cap(entity, &mut light.shadows_enabled, authored);
+        }
+    }
+}
+
+fn apply_view_distance(
+    quality: Res<DisplayQuality>,
+    mut camera_query: Query<(&mut Projection, ChangeTrackers<Projection>), With<Camera3d>>,
+) {
+    let view_distance = QualityProfile::for_quality(*quality).view_distance;
+    for (mut projection, tracker) in &mut camera_query {
+        if !quality.is_changed() && !tracker.is_added() {
+            continue;
+        }
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.far = view_distance;
+        }
+    }
+}
+
+fn apply_detail_level(
+    quality: Res<DisplayQuality>,
+    mut detail_query: Query<(&DetailLevel, &mut Visibility, ChangeTrackers<DetailLevel>)>,
+) {
+    for (detail, mut visibility, tracker) in &mut detail_query {
+        if quality.is_changed() || tracker.is_added() {
+            visibility.is_visible = detail.visible_at(*quality);
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// Steps taken along each segment when measuring it. The rail is walked by distance, so this
+// is how closely equal distances come out as equal stretches of curve.
+const SAMPLES_PER_SEGMENT: usize = 32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum RailCurve {
+    // Passes through every point
+    #[default]
+    CatmullRom,
+    // Cubic pieces, each sharing its end point with the next: start, two handles, end, two
+    // handles, end...
+    Bezier,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RailPoint {
+    pub position: Vec3,
+    // Forward speed from this point on, ships fly at their own top speed without one. Only read
+    // from points that start a segment.
+    #[serde(default)]
+    pub speed: Option<f32>,
+}
+
+// As written in level data
+#[derive(Debug, Clone, Deserialize)]
+pub struct RailDefinition {
+    #[serde(default)]
+    pub curve: RailCurve,
+    pub points: Vec<RailPoint>,
+}
+
+// Where and which way the rail points at some distance along it. In the rail's frame x is to
+// the ship's left, y up and z forward, the same axes the ship steers in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RailFrame {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+impl RailFrame {
+    // World position of a sideways and vertical offset from the rail
+    pub fn place(&self, offset: Vec2) -> Vec3 {
+        self.position + self.rotation * offset.extend(0.0)
+    }
+}
+
+// Catmull-Rom segments are converted on load, so both kinds are stored as cubic Beziers
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    controls: [Vec3; 4],
+    speed: Option<f32>,
+}
+
+impl Segment {
+    fn position(&self, t: f32) -> Vec3 {
+        let [a, b, c, d] = self.controls;
+        let u = 1.0 - t;
+        a * (u * u * u) + b * (3.0 * u * u * t) + c * (3.0 * u * t * t) + d * (t * t * t)
+    }
+
+    fn tangent(&self, t: f32) -> Vec3 {
+        let [a, b, c, d] = self.controls;
+        let u = 1.0 - t;
+        (b - a) * (3.0 * u * u) + (c - b) * (6.0 * u * t) + (d - c) * (3.0 * t * t)
+    }
+}
+
+// Distance along the rail to a point on the curve, `curve` counts whole segments before the
+// fraction of the current one
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    distance: f32,
+    curve: f32,
+}
+
+// The path ships and the camera fly along. Past the last point it carries on straight ahead,
+// so a level that runs out of rail doesn't bring everything to a halt.
+#[derive(Component, Debug, Clone)]
+pub struct RailPath {
+    segments: Vec<Segment>,
+    samples: Vec<Sample>,
+}
+
+// Straight ahead along +z from the origin, for levels without a rail
+impl Default for RailPath {
+    fn default() -> RailPath {
+        let points = [Vec3::ZERO, Vec3::Z].map(|position| RailPoint {
+            position,
+            speed: None,
+        });
+        RailPath::new(&RailDefinition {
+            curve: RailCurve::CatmullRom,
+            points: points.to_vec(),
+        })
+        .expect("straight rail should be valid")
+    }
+}
+
+impl RailPath {
+    pub fn new(definition: &RailDefinition) -> Result<RailPath, String> {
+        let points = &definition.points;
+        let segments: Vec<Segment> = match definition.curve {
+            RailCurve::CatmullRom => {
+                if points.len() < 2 {
+                    return Err("a Catmull-Rom rail needs at least 2 points".to_string());
+                }
+                // The ends are mirrored to give the first and last segments a neighbour
+                let last = points.len() - 1;
+                let at = |i: isize| -> Vec3 {
+                    if i < 0 {
+                        points[0].position * 2.0 - points[1].position
+                    } else if i as usize > last {
+                        points[last].position * 2.0 - points[last - 1].position
+                    } else {
+                        points[i as usize].position
+                    }
+                };
+                (0..last as isize)
+                    .map(|i| {
+                        let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+                        Segment {
+                            controls: [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2],
+                            speed: points[i as usize].speed,
+                        }
+                    })
+                    .collect()
+            }
+            RailCurve::Bezier => {
+                if points.len() < 4 || (points.len() - 1) % 3 != 0 {
+                    return Err(format!(
+                        "a Bezier rail needs 3n + 1 points, got {}",
+                        points.len()
+                    ));
+                }
+                points
+                    .windows(4)
+                    .step_by(3)
+                    .map(|window| Segment {
+                        controls: [0, 1, 2, 3].map(|i| window[i].position),
+                        speed: window[0].speed,
+                    })
+                    .collect()
+            }
+        };
+
+        let mut samples = vec![Sample {
+            distance: 0.0,
+            curve: 0.0,
+        }];
+        let mut distance = 0.0;
+        for (index, segment) in segments.iter().enumerate() {
+            let mut previous = segment.position(0.0);
+            for step in 1..=SAMPLES_PER_SEGMENT {
+                let t = step as f32 / SAMPLES_PER_SEGMENT as f32;
+                let position = segment.position(t);
+                distance += position.distance(previous);
+                previous = position;
+                samples.push(Sample {
+                    distance,
+                    curve: index as f32 + t,
+                });
+            }
+        }
+        if distance <= 0.0 {
+            return Err("the rail has no length".to_string());
+        }
+        Ok(RailPath { segments, samples })
+    }
+
+    pub fn length(&self) -> f32 {
+        self.samples.last().map_or(0.0, |sample| sample.distance)
+    }
+
+    // The segment and how far through it, for a distance already clamped to the rail
+    fn locate(&self, distance: f32) -> (&Segment, f32) {
+        let next = self
+            .samples
+            .partition_point(|sample| sample.distance <= distance)
+            .clamp(1, self.samples.len() - 1);
+        let (a, b) = (self.samples[next - 1], self.samples[next]);
+        let span = b.distance - a.distance;
+        let fraction = if span > 0.0 {
+            (distance - a.distance) / span
+        } else {
+            0.0
+        };
+        let curve = a.curve + (b.curve - a.curve) * fraction;
+        let index = (curve.floor() as usize).min(self.segments.len() - 1);
+        (&self.segments[index], curve - index as f32)
+    }
+
+    pub fn frame(&self, distance: f32) -> RailFrame {
+        let length = self.length();
+        let (segment, t) = self.locate(distance.clamp(0.0, length));
+        let forward = segment.tangent(t).try_normalize().unwrap_or(Vec3::Z);
+        let overshoot = (distance - length).max(0.0);
+        RailFrame {
+            position: segment.position(t) + forward * overshoot,
+            rotation: facing(forward),
+        }
+    }
+
+    // Forward speed the segment at `distance` sets, if any
+    pub fn speed(&self, distance: f32) -> Option<f32> {
+        self.locate(distance.clamp(0.0, self.length())).0.speed
+    }
+}
+
+// Turns +z onto `forward` while keeping the ship as upright as the heading allows
+fn facing(forward: Vec3) -> Quat {
+    let left = Vec3::Y.cross(forward).try_normalize().unwrap_or(Vec3::X);
+    let up = forward.cross(left);
+    Quat::from_mat3(&Mat3::from_cols(left, up, forward))
+}
+
+// Where something flying the rail is in the rail's frame: x and y are its offset from the
+// rail, z the distance travelled along it
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct RailPosition {
+    pub local: Vec3,
+}
+
+// Flies the rail back the way it came, turned to face whatever is coming along it. Steering
+// stays in the rail's axes.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Oncoming;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32, y: f32, z: f32) -> RailPoint {
+        RailPoint {
+            position: Vec3::new(x, y, z),
+            speed: None,
+        }
+    }
+
+    fn rail(curve: RailCurve, points: Vec<RailPoint>) -> RailPath {
+        RailPath::new(&RailDefinition { curve, points }).unwrap()
+    }
+
+    #[test]
+    fn default_rail_runs_along_z() {
+        let rail = RailPath::default();
+        let frame = rail.frame(250.0);
+        assert!(frame.position.distance(Vec3::new(0.0, 0.0, 250.0)) < 1e-3);
+        assert!(frame.rotation.angle_between(Quat::IDENTITY) < 1e-4);
+        assert!(frame
+            .place(Vec2::new(2.0, 3.0))
+            .abs_diff_eq(Vec3::new(2.0, 3.0, 250.0), 1e-3));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_points() {
+        let rail = rail(
+            RailCurve::CatmullRom,
+            vec![
+                point(0.0, 0.0, 0.0),
+                point(0.0, 0.0, 100.0),
+                point(50.0, 20.0, 180.0),
+                point(100.0, 20.0, 200.0),
+            ],
+        );
+        assert!(rail.frame(0.0).position.abs_diff_eq(Vec3::ZERO, 1e-3));
+        let end = rail.frame(rail.length()).position;
+        assert!(end.abs_diff_eq(Vec3::new(100.0, 20.0, 200.0), 1e-3));
+        // A straight line between the ends is the shortest way along
+        assert!(rail.length() > Vec3::new(100.0, 20.0, 200.0).length());
+    }
+
+    #[test]
+    fn distance_is_measured_along_the_curve() {
+        // Roughly a quarter circle of radius 100 turning to the left
+        let handle = 100.0 * 0.5523;
+        let rail = rail(
+            RailCurve::Bezier,
+            vec![
+                point(0.0, 0.0, 0.0),
+                point(0.0, 0.0, handle),
+                point(100.0 - handle, 0.0, 100.0),
+                point(100.0, 0.0, 100.0),
+            ],
+        );
+        let quarter = std::f32::consts::FRAC_PI_2 * 100.0;
+        assert!((rail.length() - quarter).abs() < 0.5);
+        let halfway = rail.frame(rail.length() / 2.0);
+        let expected = Vec3::new(100.0 - 100.0 * 0.5f32.sqrt(), 0.0, 100.0 * 0.5f32.sqrt());
+        assert!(halfway.position.distance(expected) < 0.5);
+        // Heading has turned halfway from +z to +x, still level
+        let forward = halfway.rotation * Vec3::Z;
+        assert!(forward.abs_diff_eq(Vec3::new(1.0, 0.0, 1.0).normalize(), 1e-2));
+        assert!((halfway.rotation * Vec3::Y).abs_diff_eq(Vec3::Y, 1e-3));
+    }
+
+    #[test]
+    fn segments_set_their_own_speed() {
+        let mut points = vec![
+            point(0.0, 0.0, 0.0),
+            point(0.0, 0.0, 100.0),
+            point(0.0, 0.0, 200.0),
+        ];
+        points[1].speed = Some(60.0);
+        let rail = rail(RailCurve::CatmullRom, points);
+        assert_eq!(rail.speed(50.0), None);
+        assert_eq!(rail.speed(150.0), Some(60.0));
+        assert_eq!(rail.speed(1000.0), Some(60.0));
+    }
+
+    #[test]
+    fn rejects_bad_definitions() {
+        let short = RailDefinition {
+            curve: RailCurve::CatmullRom,
+            points: vec![point(0.0, 0.0, 0.0)],
+        };
+        assert!(RailPath::new(&short).is_err());
+        let uneven = RailDefinition {
+            curve: RailCurve::Bezier,
+            points: (0..5).map(|z| point(0.0, 0.0, z as f32)).collect(),
+        };
+        assert!(RailPath::new(&uneven).is_err());
+        let stationary = RailDefinition {
+            curve: RailCurve::CatmullRom,
+            points: vec![point(1.0, 1.0, 1.0), point(1.0, 1.0, 1.0)],
+        };
+        assert!(RailPath::new(&stationary).is_err());
+    }
+}
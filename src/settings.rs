@@ -0,0 +1,293 @@
+use bevy::{asset::FileAssetIo, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    audio::AudioChannels,
+    catalog::{SelectedShip, SelectedSkin},
+    input::{ControlSettings, InputBindings},
+    menu::{DisplayQuality, Volume},
+};
+
+// Bumped whenever a field changes meaning. Missing fields fall back to their defaults, so only
+// files written by a newer build are refused.
+const SETTINGS_VERSION: u32 = 1;
+const SETTINGS_FILE: &str = "settings.ron";
+const APP_DIR: &str = "project-velour";
+// Overrides for the settings file location, the flag taking precedence
+const SETTINGS_ENV: &str = "VELOUR_CONFIG";
+const SETTINGS_FLAG: &str = "--config";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsFile {
+    pub version: u32,
+    pub display_quality: DisplayQuality,
+    pub volume: Volume,
+    pub audio: AudioChannels,
+    pub bindings: InputBindings,
+    pub controls: ControlSettings,
+    pub ship: Option<String>,
+    pub skin: Option<String>,
+}
+
+impl Default for SettingsFile {
+    fn default() -> SettingsFile {
+        SettingsFile {
+            version: SETTINGS_VERSION,
+            display_quality: DisplayQuality::default(),
+            volume: Volume::default(),
+            audio: AudioChannels::default(),
+            bindings: InputBindings::default(),
+            controls: ControlSettings::default(),
+            ship: None,
+            skin: None,
+        }
+    }
+}
+
+// Where settings are read from and written back to
+#[derive(Debug, Resource)]
+pub struct SettingsPath(pub PathBuf);
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let path = settings_path();
+        let settings = load_settings(&path);
+        info!("Using settings file {:?}", path);
+
+        app.insert_resource(settings.display_quality)
+            .insert_resource(settings.volume)
+            .insert_resource(settings.audio)
+            .insert_resource(settings.bindings)
+            .insert_resource(settings.controls)
+            .insert_resource(SettingsPath(path))
+            .add_system_to_stage(CoreStage::Last, save_settings);
+        // The catalog keeps these only if the hull and skin still exist
+        if let Some(ship) = settings.ship {
+            app.insert_resource(SelectedShip(ship));
+        }
+        if let Some(skin) = settings.skin {
+            app.insert_resource(SelectedSkin(skin));
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    settings_override(env::args(), env::var_os(SETTINGS_ENV)).unwrap_or_else(|| {
+        platform_config_dir()
+            .map(|dir| dir.join(APP_DIR))
+            .unwrap_or_else(FileAssetIo::get_base_path)
+            .join(SETTINGS_FILE)
+    })
+}
+
+// `--config path` or `--config=path` on the command line, otherwise the environment variable
+fn settings_override(
+    mut args: impl Iterator<Item = String>,
+    env_path: Option<OsString>,
+) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == SETTINGS_FLAG {
+            if let Some(path) = args.next() {
+                return Some(PathBuf::from(path));
+            }
+        } else if let Some(path) = arg.strip_prefix(&format!("{}=", SETTINGS_FLAG)) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    env_path.map(PathBuf::from)
+}
+
+fn platform_config_dir() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home().map(|home| home.join(".config")))
+    }
+}
+
+// Any problem reading the file leaves the defaults in place, a broken file is moved aside so
+// the next save does not silently destroy it
+fn load_settings(path: &Path) -> SettingsFile {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return SettingsFile::default(),
+    };
+    match ron::from_str::<SettingsFile>(&contents) {
+        Ok(settings) if settings.version <= SETTINGS_VERSION => migrate(settings),
+        Ok(settings) => {
+            warn!(
+                "Settings file {:?} is version {}, newer than {}. Using defaults",
+                path, settings.version, SETTINGS_VERSION
+            );
+            SettingsFile::default()
+        }
+        Err(err) => {
+            let backup = path.with_extension("ron.corrupt");
+            warn!(
+                "Settings file {:?} is corrupt ({}). Using defaults, the old file is kept at {:?}",
+                path, err, backup
+            );
+            if let Err(err) = fs::rename(path, &backup) {
+                warn!("Unable to move corrupt settings file aside: {}", err);
+            }
+            SettingsFile::default()
+        }
+    }
+}
+
+// Fields added since a file was written have already been filled in with their defaults, so an
+// older file only needs its version brought up to date. Fields that change meaning are converted
+// here.
+fn migrate(mut settings: SettingsFile) -> SettingsFile {
+    if settings.version < SETTINGS_VERSION {
+        info!(
+            "Migrating settings from version {} to {}",
+            settings.version, SETTINGS_VERSION
+        );
+        settings.version = SETTINGS_VERSION;
+    }
+    settings
+}
+
+// Writes the file whenever a setting changes, e.g. through option_button or the hangar
+fn save_settings(
+    path: Res<SettingsPath>,
+    display_quality: Res<DisplayQuality>,
+    volume: Res<Volume>,
+    audio: Res<AudioChannels>,
+    bindings: Res<InputBindings>,
+    controls: Res<ControlSettings>,
+    selected_ship: Option<Res<SelectedShip>>,
+    selected_skin: Option<Res<SelectedSkin>>,
+    mut started: Local<bool>,
+) {
+    // Everything reads as changed on the first frame, which is not worth a write
+    if !*started {
+        *started = true;
+        return;
+    }
+    let changed = display_quality.is_changed()
+        || volume.is_changed()
+        || audio.is_changed()
+        || bindings.is_changed()
+        || controls.is_changed()
+        || selected_ship
+            .as_ref()
+            .map_or(false, |ship| ship.is_changed())
+        || selected_skin
+            .as_ref()
+            .map_or(false, |skin| skin.is_changed());
+    if !changed {
+        return;
+    }
+
+    let settings = SettingsFile {
+        version: SETTINGS_VERSION,
+        display_quality: *display_quality,
+        volume: *volume,
+        audio: *audio,
+        bindings: bindings.clone(),
+        controls: *controls,
+        ship: selected_ship.map(|ship| ship.0.clone()),
+        skin: selected_skin.map(|skin| skin.0.clone()),
+    };
+    if let Err(err) = write_settings(&path.0, &settings) {
+        warn!("Unable to save settings to {:?}: {}", path.0, err);
+    }
+}
+
+fn write_settings(path: &Path, settings: &SettingsFile) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let contents = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())?;
+    // Written next to the target and renamed over it, so a crash never leaves half a file
+    let temp = path.with_extension("ron.tmp");
+    fs::write(&temp, contents).map_err(|err| err.to_string())?;
+    fs::rename(&temp, path).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fresh directory per test, so tests running in parallel never share a file
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("velour-settings-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn args(args: &'static [&'static str]) -> impl Iterator<Item = String> {
+        args.iter().map(|arg| arg.to_string())
+    }
+
+    #[test]
+    fn settings_survive_a_round_trip() {
+        let path = scratch_dir("round-trip").join(SETTINGS_FILE);
+        let settings = SettingsFile {
+            volume: Volume(3),
+            ship: Some("Scout".to_string()),
+            skin: Some("Blue".to_string()),
+            ..SettingsFile::default()
+        };
+        write_settings(&path, &settings).unwrap();
+        assert_eq!(load_settings(&path), settings);
+        assert!(!path.with_extension("ron.tmp").exists());
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up_and_replaced_by_defaults() {
+        let path = scratch_dir("corrupt").join(SETTINGS_FILE);
+        fs::write(&path, "(version: 1, volume: ").unwrap();
+        assert_eq!(load_settings(&path), SettingsFile::default());
+        assert!(!path.exists());
+        let backup = fs::read_to_string(path.with_extension("ron.corrupt")).unwrap();
+        assert_eq!(backup, "(version: 1, volume: ");
+    }
+
+    #[test]
+    fn older_version_is_migrated() {
+        let path = scratch_dir("migrate").join(SETTINGS_FILE);
+        fs::write(&path, r#"(version: 0, ship: Some("Scout"))"#).unwrap();
+        let settings = load_settings(&path);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.ship.as_deref(), Some("Scout"));
+        assert_eq!(settings.volume, Volume::default());
+    }
+
+    #[test]
+    fn config_flag_takes_precedence_over_the_environment() {
+        let env_path = Some(OsString::from("env.ron"));
+        assert_eq!(
+            settings_override(args(&["velour", "--config", "flag.ron"]), env_path.clone()),
+            Some(PathBuf::from("flag.ron"))
+        );
+        assert_eq!(
+            settings_override(args(&["velour", "--config=flag.ron"]), env_path.clone()),
+            Some(PathBuf::from("flag.ron"))
+        );
+        assert_eq!(
+            settings_override(args(&["velour"]), env_path),
+            Some(PathBuf::from("env.ron"))
+        );
+        assert_eq!(settings_override(args(&["velour"]), None), None);
+    }
+}
@@ -1,4 +1,18 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*, scene::SceneInstance, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    catalog::ShipDefinition,
+    collider::ShipColliderAsset,
+    game::OnGameScreen,
+    rail::{Oncoming, RailPath, RailPosition},
+    simulation::{SimulatedTransform, SIMULATION_DT},
+    weapon::Weapon,
+};
+use health::{Hull, Shield};
+
+pub mod health;
+pub mod kinematics;
 
 const MAX_BANK_ANGLE: f32 = 0.5;
 
@@ -10,7 +24,7 @@ pub struct MaxSpeeds {
 impl Default for MaxSpeeds {
     fn default() -> MaxSpeeds {
         MaxSpeeds {
-            max_speeds: Vec3::new(1.0, 1.0, 0.5),
+            max_speeds: Vec3::new(60.0, 60.0, 30.0),
         }
     }
 }
@@ -22,7 +36,19 @@ pub struct Accelerations {
 impl Default for Accelerations {
     fn default() -> Accelerations {
         Accelerations {
-            accelerations: Vec3::new(0.03, 0.03, 0.03),
+            accelerations: Vec3::new(108.0, 108.0, 108.0),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BankAngle {
+    pub max_bank_angle: f32,
+}
+impl Default for BankAngle {
+    fn default() -> BankAngle {
+        BankAngle {
+            max_bank_angle: MAX_BANK_ANGLE,
         }
     }
 }
@@ -38,6 +64,46 @@ impl Default for CurrentSpeeds {
         }
     }
 }
+// Forward speed multiplier while boosting
+const BOOST_FACTOR: f32 = 1.5;
+
+// Steering input in -1..=1 per axis, written by whatever is flying the ship
+#[derive(Component, Default)]
+pub struct ShipThrust {
+    pub thrust: Vec2,
+    pub boost: bool,
+}
+
+// Response tuning applied on top of the hull's own accelerations
+#[derive(Component)]
+pub struct ShipHandling {
+    pub acceleration_scale: f32,
+    pub damping: f32,
+}
+impl Default for ShipHandling {
+    fn default() -> ShipHandling {
+        ShipHandling {
+            acceleration_scale: 1.0,
+            damping: kinematics::DECELERATION_SLIDE,
+        }
+    }
+}
+
+// Base colour texture swapped onto the ship's scene once it has been instanced
+#[derive(Component)]
+pub struct ShipSkin {
+    pub texture: Handle<Image>,
+    applied: bool,
+}
+impl ShipSkin {
+    pub fn new(texture: Handle<Image>) -> ShipSkin {
+        ShipSkin {
+            texture,
+            applied: false,
+        }
+    }
+}
+
 // NOTE Placeholder code
 
 // #[derive(Component)]
@@ -51,89 +117,233 @@ impl Default for CurrentSpeeds {
 //         }
 //     }
 // }
-// #[derive(Component)]
-// pub struct Skins {
-//     ship_id: ShipId,
-//     collider: Collider,
-//     rigid_body: RigidBody,
-// }
-// impl Default for Skins {
-//     fn default() -> Skins {
-//         Skins {
-//             ship_id: ShipId {
-//                 ..Default::default()
-//             },
-//             collider: Collider::ball(1.0),
-//             rigid_body: RigidBody::Dynamic,
-//         }
-//     }
-// }
-
 // fn spawn_ship(asset_server: Res<AssetServer>) {
 //     let ship: Handle<Scene> = asset_server.load("ships/Pancake/glTF/Pancake.gltf#Scene0");
 // }
 
-// #[derive(Component)]
-// pub struct WeaponDamage {
-//     id: String,
-//     energy: f32,
-//     physical: f32,
-// }
-// #[derive(Component)]
-// pub enum WeaponType {
-//     Energy,
-//     Physical,
-// }
-// #[derive(Component)]
-// pub struct Shield {
-//     id: String,
-//     energy: f32,
-//     physical: f32,
-// }
-
 // Bundles
 #[derive(Bundle)]
 pub struct ShipBundle {
     pub max_speeds: MaxSpeeds,
     pub accelerations: Accelerations,
+    pub bank_angle: BankAngle,
     pub current_movements: CurrentSpeeds,
-    // pub skins: Skins,
+    pub thrust: ShipThrust,
+    pub handling: ShipHandling,
+    pub weapon: Weapon,
+    pub hull: Hull,
+    pub shield: Shield,
+    pub simulated: SimulatedTransform,
     #[bundle]
     pub position: TransformBundle,
     // pub scene_instance: SceneInstance,
 }
 
-// Recomended not use default
-impl Default for ShipBundle {
-    fn default() -> ShipBundle {
+impl ShipBundle {
+    pub fn from_definition(definition: &ShipDefinition) -> ShipBundle {
         ShipBundle {
             max_speeds: MaxSpeeds {
-                ..Default::default()
+                max_speeds: definition.max_speeds,
             },
             accelerations: Accelerations {
-                ..Default::default()
+                accelerations: definition.accelerations,
+            },
+            bank_angle: BankAngle {
+                max_bank_angle: definition.bank_angle,
             },
             current_movements: CurrentSpeeds {
                 ..Default::default()
             },
-            // skins: Skins {
-            //     ..Default::default()
-            // },
-            position:TransformBundle::from(Transform::from_xyz(0.0, 0.0, 0.0))
-            // scene_instance: SceneInstance {
-            //     ..Default::default()
-            // },
+            thrust: ShipThrust::default(),
+            handling: ShipHandling::default(),
+            weapon: Weapon::new(definition.weapon.clone()),
+            hull: Hull::new(definition.hull),
+            shield: Shield::new(definition.shield),
+            simulated: SimulatedTransform::new(Transform::from_xyz(0.0, 0.0, 0.0)),
+            position: TransformBundle::from(Transform::from_xyz(0.0, 0.0, 0.0)),
         }
     }
 }
 
-pub fn move_ship_system(mut query: Query<(&CurrentSpeeds, &MaxSpeeds, &mut Transform)>) {
-    for (current_speeds, max_speeds, mut transform) in query.iter_mut() {
-        transform.translation += current_speeds.current_speeds;
+// Draws a hull on an entity that already has a transform: its glTF scene, skinned once
+// instanced, or for hulls without a glTF export the OBJ collider mesh with the skin as its
+// base colour
+pub fn insert_ship_model(
+    ship: &mut EntityCommands,
+    asset_server: &AssetServer,
+    materials: &mut Assets<StandardMaterial>,
+    definition: &ShipDefinition,
+    skin: &str,
+) {
+    let texture = definition
+        .texture(skin)
+        .map(|texture| asset_server.load::<Image, _>(texture.path.as_str()));
+    ship.insert(VisibilityBundle::default());
+    match &definition.model {
+        Some(model) => {
+            ship.insert(asset_server.load::<Scene, _>(model.as_str()));
+            if let Some(texture) = texture {
+                ship.insert(ShipSkin::new(texture));
+            }
+        }
+        None => {
+            ship.insert((
+                asset_server.load::<Mesh, _>(definition.collider.mesh.as_str()),
+                materials.add(StandardMaterial {
+                    base_color_texture: texture,
+                    ..default()
+                }),
+            ));
+        }
+    }
+}
+
+// A kinematic ship for the current level, with its model, skin and collider. Whoever flies it
+// adds their own marker.
+pub fn spawn_ship<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    asset_server: &AssetServer,
+    materials: &mut Assets<StandardMaterial>,
+    definition: &ShipDefinition,
+    skin: &str,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut ship = commands.spawn(ShipBundle::from_definition(definition));
+    insert_ship_model(&mut ship, asset_server, materials, definition, skin);
+    ship.insert(RigidBody::KinematicPositionBased)
+        // Collider is attached by the collider plugin once this finishes loading
+        .insert(asset_server.load::<ShipColliderAsset, _>(definition.definition_path()))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        // Kinematic against kinematic and fixed bodies is off by default, scenery is fixed
+        .insert(
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC
+                | ActiveCollisionTypes::KINEMATIC_STATIC,
+        )
+        .insert(OnGameScreen);
+    ship
+}
+
+// Runs once per simulation tick. Ships on the rail move in its frame, the rest straight along
+// world z.
+pub fn move_ship_system(
+    mut query: Query<(
+        &mut CurrentSpeeds,
+        &ShipThrust,
+        &MaxSpeeds,
+        &Accelerations,
+        &ShipHandling,
+        &BankAngle,
+        &mut SimulatedTransform,
+        Option<&mut RailPosition>,
+        Option<&Oncoming>,
+    )>,
+    rail_query: Query<&RailPath>,
+) {
+    let rail = rail_query.get_single().ok();
+    for (
+        mut current_speeds,
+        thrust,
+        max_speeds,
+        accelerations,
+        handling,
+        bank_angle,
+        mut simulated,
+        mut rail_position,
+        oncoming,
+    ) in query.iter_mut()
+    {
+        let on_rail = rail.zip(rail_position.as_deref_mut());
+        let oncoming = oncoming.is_some() && on_rail.is_some();
+        let mut limits = max_speeds.max_speeds;
+        // Segments with a speed of their own override the ship's top speed
+        let rail_speed = on_rail
+            .as_ref()
+            .and_then(|(rail, position)| rail.speed(position.local.z));
+        if let Some(speed) = rail_speed {
+            limits.z = speed;
+        }
+        if thrust.boost {
+            limits.z *= BOOST_FACTOR;
+        }
+        let position = match &on_rail {
+            Some((_, position)) => position.local,
+            None => simulated.current.translation,
+        };
+        let motion = kinematics::step(
+            kinematics::Motion {
+                position,
+                velocity: current_speeds.current_speeds,
+            },
+            thrust.thrust,
+            limits,
+            accelerations.accelerations * handling.acceleration_scale,
+            handling.damping,
+            SIMULATION_DT,
+        );
+        current_speeds.current_speeds = motion.velocity;
         // info!("Moving check {:?}", transform.translation);
-        let rot_z = (current_speeds.current_speeds.x / max_speeds.max_speeds.x) * -MAX_BANK_ANGLE;
-        let rot_x = (current_speeds.current_speeds.y / max_speeds.max_speeds.y) * -MAX_BANK_ANGLE;
+        // Turned around, the ship's own left is the rail's right
+        let sideways = if oncoming {
+            -current_speeds.current_speeds.x
+        } else {
+            current_speeds.current_speeds.x
+        };
+        let rot_z = (sideways / max_speeds.max_speeds.x) * -bank_angle.max_bank_angle;
+        let rot_x = (current_speeds.current_speeds.y / max_speeds.max_speeds.y)
+            * -bank_angle.max_bank_angle;
         let rotation_percent = Quat::from_euler(EulerRot::XYZ, rot_x, 0.0, rot_z);
-        transform.rotation = rotation_percent;
+        match on_rail {
+            Some((rail, position)) => {
+                let mut local = motion.position;
+                let mut heading = Quat::IDENTITY;
+                if oncoming {
+                    local.z = position.local.z - (motion.position.z - position.local.z);
+                    heading = Quat::from_rotation_y(std::f32::consts::PI);
+                }
+                position.local = local;
+                let frame = rail.frame(position.local.z);
+                simulated.current.translation = frame.place(position.local.truncate());
+                simulated.current.rotation = frame.rotation * heading * rotation_percent;
+            }
+            None => {
+                simulated.current.translation = motion.position;
+                simulated.current.rotation = rotation_percent;
+            }
+        }
+    }
+}
+
+pub fn apply_ship_skin_system(
+    mut skin_query: Query<(&mut ShipSkin, &SceneInstance)>,
+    mut material_query: Query<&mut Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    scene_spawner: Res<SceneSpawner>,
+) {
+    for (mut skin, instance) in &mut skin_query {
+        if skin.applied || !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+        // Scene materials are shared between instances, so each one is copied before recolouring
+        let mut skinned: HashMap<Handle<StandardMaterial>, Handle<StandardMaterial>> =
+            HashMap::default();
+        for entity in scene_spawner.iter_instance_entities(**instance) {
+            if let Ok(mut material_handle) = material_query.get_mut(entity) {
+                let skinned_handle = match skinned.get(&*material_handle) {
+                    Some(handle) => handle.clone(),
+                    None => {
+                        let Some(material) = materials.get(&*material_handle) else {
+                            continue;
+                        };
+                        let mut material = material.clone();
+                        material.base_color_texture = Some(skin.texture.clone());
+                        let handle = materials.add(material);
+                        skinned.insert(material_handle.clone(), handle.clone());
+                        handle
+                    }
+                };
+                *material_handle = skinned_handle;
+            }
+        }
+        skin.applied = true;
     }
 }
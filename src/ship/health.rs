@@ -0,0 +1,383 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    collision::{self, GroundHit, ObstacleHit},
+    player::Player,
+    simulation::{self, SimulationLabel, SimulationStage, SIMULATION_DT},
+    weapon::{ProjectileHit, WeaponDamage},
+    GameState,
+};
+
+// Fraction of each kind of damage shrugged off, 0 takes it all and 1 ignores it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Resistance {
+    pub energy: f32,
+    pub physical: f32,
+}
+
+impl Resistance {
+    fn reduce(&self, damage: WeaponDamage) -> WeaponDamage {
+        WeaponDamage {
+            energy: damage.energy * (1.0 - self.energy.clamp(0.0, 1.0)),
+            physical: damage.physical * (1.0 - self.physical.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct HullSettings {
+    pub capacity: f32,
+    pub resistance: Resistance,
+}
+
+impl Default for HullSettings {
+    fn default() -> HullSettings {
+        HullSettings {
+            capacity: 100.0,
+            resistance: Resistance {
+                energy: 0.0,
+                physical: 0.2,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ShieldSettings {
+    pub capacity: f32,
+    // Seconds without being hit before the shield starts to recharge
+    pub regen_delay: f32,
+    // Strength regained per second once recharging
+    pub regen_rate: f32,
+    pub resistance: Resistance,
+}
+
+impl Default for ShieldSettings {
+    fn default() -> ShieldSettings {
+        ShieldSettings {
+            capacity: 50.0,
+            regen_delay: 3.0,
+            regen_rate: 10.0,
+            resistance: Resistance {
+                energy: 0.25,
+                physical: 0.0,
+            },
+        }
+    }
+}
+
+// The ship is destroyed when this reaches zero
+#[derive(Component, Debug, Clone)]
+pub struct Hull {
+    pub settings: HullSettings,
+    pub health: f32,
+}
+
+impl Hull {
+    pub fn new(settings: HullSettings) -> Hull {
+        Hull {
+            settings,
+            health: settings.capacity,
+        }
+    }
+
+    pub fn destroyed(&self) -> bool {
+        self.health <= 0.0
+    }
+}
+
+// Takes damage ahead of the hull and recharges after a quiet spell
+#[derive(Component, Debug, Clone)]
+pub struct Shield {
+    pub settings: ShieldSettings,
+    pub strength: f32,
+    since_hit: f32,
+}
+
+impl Shield {
+    pub fn new(settings: ShieldSettings) -> Shield {
+        Shield {
+            settings,
+            strength: settings.capacity,
+            since_hit: settings.regen_delay,
+        }
+    }
+
+    fn recharge(&mut self, dt: f32) {
+        self.since_hit += dt;
+        if self.since_hit >= self.settings.regen_delay {
+            self.strength =
+                (self.strength + self.settings.regen_rate * dt).min(self.settings.capacity);
+        }
+    }
+}
+
+// Damage dealt to ships that fly into this obstacle or ground
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ContactDamage(pub WeaponDamage);
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShipDamaged {
+    pub ship: Entity,
+    // Ship that fired the shot, or the obstacle flown into
+    pub source: Entity,
+    pub shield: f32,
+    pub hull: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShipDestroyed {
+    pub ship: Entity,
+    pub source: Entity,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct DamageTaken {
+    shield: f32,
+    hull: f32,
+}
+
+// The shield soaks up what it can at its own resistances. Whatever share of the hit it could not
+// hold goes through to the hull, at the hull's resistances.
+fn take_damage(damage: WeaponDamage, shield: Option<&mut Shield>, hull: &mut Hull) -> DamageTaken {
+    let mut through = damage;
+    let mut taken = DamageTaken::default();
+    if let Some(shield) = shield {
+        shield.since_hit = 0.0;
+        let reduced = shield.settings.resistance.reduce(damage);
+        let total = reduced.energy + reduced.physical;
+        if total > 0.0 {
+            taken.shield = total.min(shield.strength);
+            shield.strength -= taken.shield;
+            let unabsorbed = 1.0 - taken.shield / total;
+            through = WeaponDamage {
+                energy: damage.energy * unabsorbed,
+                physical: damage.physical * unabsorbed,
+            };
+        }
+    }
+    let reduced = hull.settings.resistance.reduce(through);
+    taken.hull = (reduced.energy + reduced.physical).min(hull.health);
+    hull.health -= taken.hull;
+    taken
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShipDamaged>()
+            .add_event::<ShipDestroyed>()
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_run_criteria(simulation::game_running)
+                    .with_system(recharge_shields.after(SimulationLabel::Weapons))
+                    .with_system(resolve_damage.after(collision::dispatch_collisions))
+                    .with_system(remove_destroyed_ships.after(resolve_damage))
+                    .with_system(end_game_on_player_loss.after(resolve_damage)),
+            );
+    }
+}
+
+fn recharge_shields(mut query: Query<(&mut Shield, &Hull)>) {
+    for (mut shield, hull) in &mut query {
+        if !hull.destroyed() {
+            shield.recharge(SIMULATION_DT);
+        }
+    }
+}
+
+// Contacts arrive already sorted out by the collision dispatch, which also drops hits on
+// invulnerable ships
+fn resolve_damage(
+    mut hits: EventReader<ProjectileHit>,
+    mut obstacle_hits: EventReader<ObstacleHit>,
+    mut ground_hits: EventReader<GroundHit>,
+    contact_query: Query<&ContactDamage>,
+    mut ship_query: Query<(&mut Hull, Option<&mut Shield>)>,
+    mut damaged: EventWriter<ShipDamaged>,
+    mut destroyed: EventWriter<ShipDestroyed>,
+) {
+    let shots = hits.iter().map(|hit| (hit.target, hit.owner, hit.damage));
+    let contacts = obstacle_hits
+        .iter()
+        .map(|hit| (hit.ship, hit.obstacle))
+        .chain(ground_hits.iter().map(|hit| (hit.ship, hit.ground)))
+        .filter_map(|(ship, source)| {
+            let ContactDamage(damage) = contact_query.get(source).ok()?;
+            Some((ship, source, *damage))
+        });
+
+    for (ship, source, damage) in shots.chain(contacts) {
+        let Ok((mut hull, shield)) = ship_query.get_mut(ship) else {
+            continue;
+        };
+        if hull.destroyed() {
+            continue;
+        }
+        let taken = take_damage(damage, shield.map(|shield| shield.into_inner()), &mut hull);
+        damaged.send(ShipDamaged {
+            ship,
+            source,
+            shield: taken.shield,
+            hull: taken.hull,
+        });
+        if hull.destroyed() {
+            destroyed.send(ShipDestroyed { ship, source });
+        }
+    }
+}
+
+// The player's ship stays until the game ends, anything else is simply gone
+fn remove_destroyed_ships(
+    mut commands: Commands,
+    mut destroyed: EventReader<ShipDestroyed>,
+    player_query: Query<(), With<Player>>,
+) {
+    for event in destroyed.iter() {
+        if !player_query.contains(event.ship) {
+            commands.entity(event.ship).despawn_recursive();
+        }
+    }
+}
+
+// Losing the ship ends the run, leaving the game screen takes everything in it down
+fn end_game_on_player_loss(
+    mut destroyed: EventReader<ShipDestroyed>,
+    player_query: Query<(), With<Player>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if destroyed
+        .iter()
+        .any(|event| player_query.contains(event.ship))
+    {
+        info!("Player ship destroyed");
+        if let Err(err) = game_state.set(GameState::Menu) {
+            warn!("Unable to leave the game: {:?}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn damage(energy: f32, physical: f32) -> WeaponDamage {
+        WeaponDamage { energy, physical }
+    }
+
+    fn hull() -> Hull {
+        Hull::new(HullSettings {
+            capacity: 100.0,
+            resistance: Resistance::default(),
+        })
+    }
+
+    fn shield(capacity: f32) -> Shield {
+        Shield::new(ShieldSettings {
+            capacity,
+            regen_delay: 2.0,
+            regen_rate: 5.0,
+            resistance: Resistance::default(),
+        })
+    }
+
+    #[test]
+    fn shield_takes_damage_before_hull() {
+        let mut hull = hull();
+        let mut shield = shield(50.0);
+        let taken = take_damage(damage(20.0, 10.0), Some(&mut shield), &mut hull);
+        assert_eq!(
+            taken,
+            DamageTaken {
+                shield: 30.0,
+                hull: 0.0
+            }
+        );
+        assert_eq!(shield.strength, 20.0);
+        assert_eq!(hull.health, 100.0);
+    }
+
+    #[test]
+    fn overflow_goes_to_hull() {
+        let mut hull = hull();
+        let mut shield = shield(10.0);
+        let taken = take_damage(damage(30.0, 10.0), Some(&mut shield), &mut hull);
+        assert_eq!(
+            taken,
+            DamageTaken {
+                shield: 10.0,
+                hull: 30.0
+            }
+        );
+        assert_eq!(shield.strength, 0.0);
+        assert_eq!(hull.health, 70.0);
+    }
+
+    #[test]
+    fn resistances_reduce_each_kind() {
+        let mut hull = Hull::new(HullSettings {
+            capacity: 100.0,
+            resistance: Resistance {
+                energy: 0.5,
+                physical: 0.0,
+            },
+        });
+        let taken = take_damage(damage(20.0, 10.0), None, &mut hull);
+        assert_eq!(taken.hull, 20.0);
+        assert!(!hull.destroyed());
+        take_damage(damage(0.0, 500.0), None, &mut hull);
+        assert_eq!(hull.health, 0.0);
+        assert!(hull.destroyed());
+    }
+
+    #[test]
+    fn shield_waits_before_recharging() {
+        let mut hull = hull();
+        let mut shield = shield(50.0);
+        take_damage(damage(20.0, 0.0), Some(&mut shield), &mut hull);
+        shield.recharge(1.0);
+        assert_eq!(shield.strength, 30.0);
+        shield.recharge(1.0);
+        shield.recharge(1.0);
+        assert_eq!(shield.strength, 40.0);
+        shield.recharge(10.0);
+        assert_eq!(shield.strength, 50.0);
+    }
+
+    #[test]
+    fn losing_the_player_ends_the_game() {
+        let mut app = App::new();
+        app.add_state(GameState::Game)
+            .add_event::<ShipDestroyed>()
+            .add_system(end_game_on_player_loss);
+        let player = app.world.spawn(Player).id();
+        let enemy = app.world.spawn_empty().id();
+        let state = |app: &App| app.world.resource::<State<GameState>>().current().clone();
+
+        app.world
+            .resource_mut::<Events<ShipDestroyed>>()
+            .send(ShipDestroyed {
+                ship: enemy,
+                source: player,
+            });
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Game);
+
+        app.world
+            .resource_mut::<Events<ShipDestroyed>>()
+            .send(ShipDestroyed {
+                ship: player,
+                source: enemy,
+            });
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Menu);
+    }
+}
@@ -0,0 +1,215 @@
+use bevy::prelude::{Vec2, Vec3};
+
+// Ship motion in units per second. Each step is integrated in closed form, so a ship follows
+// the same path whether it is stepped at 30 Hz or 144 Hz.
+
+// Default exponential decay rate of sideways drift once thrust stops, per second.
+// Matches the old slide of dividing the speed by 1.2 every 60 Hz frame (60 * ln 1.2).
+pub const DECELERATION_SLIDE: f32 = 10.94;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Motion {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+// Advances a ship by `dt` seconds. `thrust` is the steering input in -1..=1 per axis, x to the
+// ship's left and y upward; forward speed is always held at the z maximum. `damping` is the
+// drift decay rate for axes without thrust.
+pub fn step(
+    motion: Motion,
+    thrust: Vec2,
+    max_speeds: Vec3,
+    accelerations: Vec3,
+    damping: f32,
+    dt: f32,
+) -> Motion {
+    let (x, velocity_x) = step_axis(
+        motion.position.x,
+        motion.velocity.x,
+        thrust.x,
+        max_speeds.x,
+        accelerations.x,
+        damping,
+        dt,
+    );
+    let (y, velocity_y) = step_axis(
+        motion.position.y,
+        motion.velocity.y,
+        thrust.y,
+        max_speeds.y,
+        accelerations.y,
+        damping,
+        dt,
+    );
+    Motion {
+        position: Vec3::new(x, y, motion.position.z + max_speeds.z * dt),
+        velocity: Vec3::new(velocity_x, velocity_y, max_speeds.z),
+    }
+}
+
+fn step_axis(
+    position: f32,
+    velocity: f32,
+    thrust: f32,
+    max_speed: f32,
+    acceleration: f32,
+    damping: f32,
+    dt: f32,
+) -> (f32, f32) {
+    if thrust == 0.0 {
+        if damping <= 0.0 {
+            return (position + velocity * dt, velocity);
+        }
+        // v(t) = v0 e^(-kt), which travels v0 (1 - e^(-kt)) / k
+        let decay = (-damping * dt).exp();
+        return (
+            position + velocity * (1.0 - decay) / damping,
+            velocity * decay,
+        );
+    }
+
+    // A half-pushed stick asks for half the speed and gets there at half the rate
+    let thrust = thrust.clamp(-1.0, 1.0);
+    let acceleration = acceleration * thrust.abs();
+    if acceleration <= 0.0 {
+        return (position + velocity * dt, velocity);
+    }
+
+    // Accelerate toward the speed the input asks for, then hold it for the rest of the step
+    let target = thrust * max_speed;
+    let gap = target - velocity;
+    let ramp_time = (gap.abs() / acceleration).min(dt);
+    let ramp_acceleration = gap.signum() * acceleration;
+    let end_velocity = if ramp_time < dt {
+        target
+    } else {
+        velocity + ramp_acceleration * dt
+    };
+
+    let ramp_distance = velocity * ramp_time + 0.5 * ramp_acceleration * ramp_time * ramp_time;
+    (
+        position + ramp_distance + end_velocity * (dt - ramp_time),
+        end_velocity,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_SPEEDS: Vec3 = Vec3::new(60.0, 60.0, 30.0);
+    const ACCELERATIONS: Vec3 = Vec3::new(108.0, 108.0, 108.0);
+
+    // Thrust held over each span of seconds: climb and bank, coast, then swing back
+    const SCRIPT: [(f32, Vec2); 3] = [
+        (1.0, Vec2::new(1.0, -1.0)),
+        (1.0, Vec2::ZERO),
+        (0.5, Vec2::new(-1.0, 0.0)),
+    ];
+
+    // Runs the script at `hz` and records the motion at the end of every span
+    fn simulate(hz: u32) -> Vec<Motion> {
+        let dt = 1.0 / hz as f32;
+        let mut motion = Motion::default();
+        SCRIPT
+            .iter()
+            .map(|(seconds, thrust)| {
+                for _ in 0..(seconds * hz as f32).round() as u32 {
+                    motion = step(
+                        motion,
+                        *thrust,
+                        MAX_SPEEDS,
+                        ACCELERATIONS,
+                        DECELERATION_SLIDE,
+                        dt,
+                    );
+                }
+                motion
+            })
+            .collect()
+    }
+
+    #[test]
+    fn trajectory_is_independent_of_frame_rate() {
+        let reference = simulate(60);
+        for hz in [30, 144] {
+            for (span, (expected, actual)) in reference.iter().zip(simulate(hz)).enumerate() {
+                assert!(
+                    expected.position.distance(actual.position) < 1e-2,
+                    "{} Hz span {}: {:?} != {:?}",
+                    hz,
+                    span,
+                    actual.position,
+                    expected.position
+                );
+                assert!(
+                    expected.velocity.distance(actual.velocity) < 1e-3,
+                    "{} Hz span {}: {:?} != {:?}",
+                    hz,
+                    span,
+                    actual.velocity,
+                    expected.velocity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn accelerates_to_max_speed_without_overshoot() {
+        // 60 / 108 seconds to reach max speed, covering v^2 / 2a before cruising
+        let motion = step(
+            Motion::default(),
+            Vec2::X,
+            MAX_SPEEDS,
+            ACCELERATIONS,
+            DECELERATION_SLIDE,
+            1.0,
+        );
+        assert_eq!(motion.velocity.x, MAX_SPEEDS.x);
+        let ramp = MAX_SPEEDS.x / ACCELERATIONS.x;
+        let expected = 0.5 * MAX_SPEEDS.x * ramp + MAX_SPEEDS.x * (1.0 - ramp);
+        assert!((motion.position.x - expected).abs() < 1e-3);
+        assert_eq!(motion.position.z, MAX_SPEEDS.z);
+    }
+
+    #[test]
+    fn analog_thrust_scales_speed_and_acceleration() {
+        let motion = step(
+            Motion::default(),
+            Vec2::new(0.5, 0.0),
+            MAX_SPEEDS,
+            ACCELERATIONS,
+            DECELERATION_SLIDE,
+            0.1,
+        );
+        assert!((motion.velocity.x - 0.5 * ACCELERATIONS.x * 0.1).abs() < 1e-4);
+        let motion = step(
+            motion,
+            Vec2::new(0.5, 0.0),
+            MAX_SPEEDS,
+            ACCELERATIONS,
+            DECELERATION_SLIDE,
+            10.0,
+        );
+        assert_eq!(motion.velocity.x, 0.5 * MAX_SPEEDS.x);
+    }
+
+    #[test]
+    fn drift_matches_old_per_frame_slide() {
+        let motion = Motion {
+            velocity: Vec3::new(1.2, -1.2, 0.0),
+            ..Default::default()
+        };
+        let motion = step(
+            motion,
+            Vec2::ZERO,
+            MAX_SPEEDS,
+            ACCELERATIONS,
+            DECELERATION_SLIDE,
+            1.0 / 60.0,
+        );
+        assert!((motion.velocity.x - 1.0).abs() < 1e-3);
+        assert!((motion.velocity.y + 1.0).abs() < 1e-3);
+    }
+}
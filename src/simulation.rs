@@ -0,0 +1,197 @@
+use bevy::{
+    ecs::schedule::ShouldRun,
+    prelude::*,
+    time::{FixedTimestep, FixedTimesteps},
+    transform::TransformSystem,
+};
+use bevy_rapier3d::{
+    prelude::{
+        NoUserData, PhysicsStages, RapierConfiguration, RapierContext, RapierPhysicsPlugin,
+        RapierRigidBodyHandle, RigidBody, TimestepMode,
+    },
+    utils::transform_to_iso,
+};
+
+use crate::GameState;
+
+// Gameplay advances in fixed ticks so a run plays out the same at any frame rate
+pub const SIMULATION_HZ: f64 = 60.0;
+pub const SIMULATION_DT: f32 = 1.0 / SIMULATION_HZ as f32;
+const SIMULATION_TIMESTEP: &str = "simulation";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct SimulationStage;
+
+// Order of the work done inside a single tick
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SimulationLabel {
+    Input,
+    Movement,
+    Weapons,
+    Camera,
+    // Rapier's own systems, each part needing the one before it done
+    PhysicsSync,
+    PhysicsFlush,
+    PhysicsStep,
+    PhysicsWriteback,
+}
+
+// Authoritative transform of anything the simulation moves. `Transform` is only written by
+// `interpolate_transforms`, blending the last two ticks for smooth rendering.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct SimulatedTransform {
+    pub previous: Transform,
+    pub current: Transform,
+}
+
+impl SimulatedTransform {
+    pub fn new(transform: Transform) -> SimulatedTransform {
+        SimulatedTransform {
+            previous: transform,
+            current: transform,
+        }
+    }
+
+    // Moves without blending from the old position, e.g. on respawn
+    pub fn teleport(&mut self, transform: Transform) {
+        *self = SimulatedTransform::new(transform);
+    }
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_stage_after(
+            CoreStage::Update,
+            SimulationStage,
+            SystemStage::parallel().with_run_criteria(
+                FixedTimestep::steps_per_second(SIMULATION_HZ).with_label(SIMULATION_TIMESTEP),
+            ),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            SystemSet::new()
+                .with_run_criteria(game_running)
+                .with_system(store_previous_transforms.before(SimulationLabel::Input)),
+        )
+        .add_startup_system(configure_physics)
+        // Rapier is added without its stages and steps here instead, once per tick after
+        // everything has moved. Contacts are read after PhysicsStep.
+        .add_system_set_to_stage(
+            SimulationStage,
+            physics_systems(PhysicsStages::SyncBackend)
+                .label(SimulationLabel::PhysicsSync)
+                .after(SimulationLabel::Movement)
+                .after(SimulationLabel::Weapons),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            physics_systems(PhysicsStages::SyncBackendFlush)
+                .label(SimulationLabel::PhysicsFlush)
+                .after(SimulationLabel::PhysicsSync),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            SystemSet::new()
+                .with_run_criteria(game_running)
+                .with_system(
+                    sync_kinematic_bodies
+                        .after(SimulationLabel::PhysicsFlush)
+                        .before(SimulationLabel::PhysicsStep),
+                ),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            physics_systems(PhysicsStages::StepSimulation)
+                .label(SimulationLabel::PhysicsStep)
+                .after(SimulationLabel::PhysicsFlush),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            physics_systems(PhysicsStages::Writeback)
+                .label(SimulationLabel::PhysicsWriteback)
+                .after(SimulationLabel::PhysicsStep),
+        )
+        // Despawns are still picked up at the end of every frame
+        .add_stage_before(
+            CoreStage::Last,
+            PhysicsStages::DetectDespawn,
+            SystemStage::parallel().with_system_set(
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::DetectDespawn),
+            ),
+        )
+        // Paused ships hold their last blended pose instead of drifting with the accumulator
+        .add_system_set_to_stage(
+            CoreStage::PostUpdate,
+            SystemSet::new()
+                .with_run_criteria(game_running)
+                .with_system(interpolate_transforms.before(TransformSystem::TransformPropagate)),
+        );
+    }
+}
+
+// Ticks only advance while actually playing, so pausing and loading freeze the simulation
+pub fn game_running(state: Res<State<GameState>>) -> ShouldRun {
+    if *state.current() == GameState::Game {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+fn physics_systems(stage: PhysicsStages) -> SystemSet {
+    RapierPhysicsPlugin::<NoUserData>::get_systems(stage).with_run_criteria(game_running)
+}
+
+// SimulationStage does the accumulating, so Rapier takes exactly one step per tick
+fn configure_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.timestep_mode = TimestepMode::Fixed {
+        dt: SIMULATION_DT,
+        substeps: 1,
+    };
+}
+
+// Rapier would otherwise move kinematic bodies to their GlobalTransform, which is the blended
+// render pose from last frame and trails the simulation by up to a tick
+fn sync_kinematic_bodies(
+    mut context: ResMut<RapierContext>,
+    query: Query<(&SimulatedTransform, &RigidBody, &RapierRigidBodyHandle)>,
+) {
+    let scale = context.physics_scale();
+    for (simulated, rigid_body, handle) in &query {
+        if *rigid_body != RigidBody::KinematicPositionBased {
+            continue;
+        }
+        if let Some(body) = context.bodies.get_mut(handle.0) {
+            body.set_next_kinematic_position(transform_to_iso(&simulated.current, scale));
+        }
+    }
+}
+
+fn store_previous_transforms(mut query: Query<&mut SimulatedTransform>) {
+    for mut simulated in &mut query {
+        simulated.previous = simulated.current;
+    }
+}
+
+fn interpolate_transforms(
+    fixed_timesteps: Res<FixedTimesteps>,
+    mut query: Query<(&SimulatedTransform, &mut Transform)>,
+) {
+    let alpha = fixed_timesteps
+        .get(SIMULATION_TIMESTEP)
+        .map_or(1.0, |timestep| timestep.overstep_percentage() as f32)
+        .clamp(0.0, 1.0);
+    for (simulated, mut transform) in &mut query {
+        transform.translation = simulated
+            .previous
+            .translation
+            .lerp(simulated.current.translation, alpha);
+        transform.rotation = simulated
+            .previous
+            .rotation
+            .slerp(simulated.current.rotation, alpha);
+        transform.scale = simulated.current.scale;
+    }
+}
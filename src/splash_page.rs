@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use bevy::{prelude::*, utils::tracing::Instrument};
 
-use super::{despawn_screen, GameState, MENU_TEXT_COLOR};
+use super::{despawn_screen, GameState};
 
 #[derive(Component)]
 struct OnSplashScreen;
@@ -0,0 +1,223 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{de::Error, Deserialize, Deserializer};
+
+// Watched for changes, saving it restyles whatever UI is on screen
+const THEME_FILE: &str = "themes/default.theme.ron";
+// Used until the file has loaded, or if it never does
+const BUILTIN_THEME: &str = include_str!("../assets/themes/default.theme.ron");
+
+// Colours, fonts and sizes every menu and HUD builder reads. Sizes and spacing take effect the
+// next time a screen is built, colours and fonts also change on screens already showing.
+#[derive(Debug, Clone, Deserialize, TypeUuid, Resource)]
+#[uuid = "9b2e4c71-5f0d-4a86-b3e9-6d1f7a2c8e45"]
+pub struct UiTheme {
+    pub palette: Palette,
+    pub fonts: Fonts,
+    pub sizes: Sizes,
+    pub spacing: Spacing,
+    #[serde(skip)]
+    text_font: Handle<Font>,
+    #[serde(skip)]
+    title_font: Handle<Font>,
+}
+
+// Written as hex strings, "RRGGBB" or "RRGGBBAA"
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Palette {
+    #[serde(deserialize_with = "hex_color")]
+    pub background: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub text: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub normal_button: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub hovered_button: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub hovered_pressed_button: Color,
+    #[serde(deserialize_with = "hex_color")]
+    pub pressed_button: Color,
+}
+
+// Paths relative to the assets folder
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fonts {
+    pub text: String,
+    pub title: String,
+}
+
+// Font sizes, and the default button size
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Sizes {
+    pub title: f32,
+    pub button: f32,
+    pub text: f32,
+    pub small: f32,
+    pub button_width: f32,
+    pub button_height: f32,
+}
+
+// Margins around buttons, `compact` for rows of small ones
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Spacing {
+    pub button: f32,
+    pub compact: f32,
+}
+
+// Palette entry behind a node's background, tagged when the widget is spawned. Buttons and
+// sliders are left untagged, their colour follows their interaction state instead.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteColor {
+    Background,
+    Text,
+    NormalButton,
+    PressedButton,
+}
+
+// Text drawn in the palette's text colour, in the body or the title font
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemedText {
+    Body,
+    Title,
+}
+
+pub fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    Color::hex(hex.trim_start_matches('#')).map_err(D::Error::custom)
+}
+
+impl Palette {
+    pub fn color(&self, role: PaletteColor) -> Color {
+        match role {
+            PaletteColor::Background => self.background,
+            PaletteColor::Text => self.text,
+            PaletteColor::NormalButton => self.normal_button,
+            PaletteColor::PressedButton => self.pressed_button,
+        }
+    }
+}
+
+impl UiTheme {
+    pub fn text_font(&self) -> Handle<Font> {
+        self.text_font.clone()
+    }
+
+    pub fn title_font(&self) -> Handle<Font> {
+        self.title_font.clone()
+    }
+
+    pub fn text_style(&self, font_size: f32) -> TextStyle {
+        TextStyle {
+            font: self.text_font(),
+            font_size,
+            color: self.palette.text,
+        }
+    }
+
+    pub fn font(&self, role: ThemedText) -> Handle<Font> {
+        match role {
+            ThemedText::Body => self.text_font(),
+            ThemedText::Title => self.title_font(),
+        }
+    }
+
+    fn load_fonts(&mut self, asset_server: &AssetServer) {
+        self.text_font = asset_server.load(&self.fonts.text);
+        self.title_font = asset_server.load(&self.fonts.title);
+    }
+}
+
+impl FromWorld for UiTheme {
+    fn from_world(world: &mut World) -> UiTheme {
+        let mut theme: UiTheme =
+            ron::de::from_str(BUILTIN_THEME).expect("built-in theme should parse");
+        theme.load_fonts(world.resource::<AssetServer>());
+        theme
+    }
+}
+
+#[derive(Resource)]
+struct ThemeHandle(Handle<UiTheme>);
+
+impl FromWorld for ThemeHandle {
+    fn from_world(world: &mut World) -> ThemeHandle {
+        ThemeHandle(world.resource::<AssetServer>().load(THEME_FILE))
+    }
+}
+
+#[derive(Default)]
+pub struct UiThemeLoader;
+
+impl AssetLoader for UiThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let theme: UiTheme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<UiTheme>()
+            .init_asset_loader::<UiThemeLoader>()
+            .init_resource::<UiTheme>()
+            .init_resource::<ThemeHandle>()
+            .add_system(apply_theme);
+    }
+}
+
+// Swaps in the theme file whenever it (re)loads, recolouring nodes by the role they were tagged
+// with. Buttons pick up the new palette from `parts`, which tracks their state.
+fn apply_theme(
+    mut events: EventReader<AssetEvent<UiTheme>>,
+    handle: Res<ThemeHandle>,
+    themes: Res<Assets<UiTheme>>,
+    asset_server: Res<AssetServer>,
+    mut theme: ResMut<UiTheme>,
+    mut color_query: Query<(&PaletteColor, &mut BackgroundColor)>,
+    mut text_query: Query<(&ThemedText, &mut Text)>,
+) {
+    for event in events.iter() {
+        let (AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }) =
+            event
+        else {
+            continue;
+        };
+        if *changed != handle.0 {
+            continue;
+        }
+        let Some(loaded) = themes.get(changed) else {
+            continue;
+        };
+        let mut next = loaded.clone();
+        next.load_fonts(&asset_server);
+
+        for (role, mut color) in &mut color_query {
+            color.0 = next.palette.color(*role);
+        }
+        for (role, mut text) in &mut text_query {
+            for section in &mut text.sections {
+                section.style.color = next.palette.text;
+                section.style.font = next.font(*role);
+            }
+        }
+        info!("Applied UI theme {}", THEME_FILE);
+        *theme = next;
+    }
+}
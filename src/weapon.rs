@@ -0,0 +1,438 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+use crate::{
+    audio::SoundEffect,
+    collision,
+    game::OnGameScreen,
+    simulation::{self, SimulatedTransform, SimulationLabel, SimulationStage, SIMULATION_DT},
+    GameState,
+};
+
+// Projectiles made up front when a level starts, the pool grows past this if it has to
+const POOL_PREWARM: usize = 64;
+// Shots leave from just ahead of the hull rather than its centre
+const MUZZLE_OFFSET: f32 = 2.0;
+const PROJECTILE_RADIUS: f32 = 0.15;
+// Heat at which an energy weapon locks until it has cooled right down
+const MAX_HEAT: f32 = 1.0;
+// Turn between consecutive shots of the spread pattern
+const GOLDEN_ANGLE: f32 = PI * 0.763_932;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum WeaponType {
+    #[default]
+    Energy,
+    Physical,
+}
+
+// Split so shields and hulls can resist each kind differently
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct WeaponDamage {
+    pub energy: f32,
+    pub physical: f32,
+}
+
+// What limits sustained fire
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum WeaponSupply {
+    // Heat added per shot and bled off per second
+    Heat { per_shot: f32, cooling: f32 },
+    // Rounds carried into the level
+    Ammo(u32),
+}
+
+// A hull's gun as written in its .ship.ron, see `ShipDefinition::weapon`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WeaponSettings {
+    pub weapon_type: WeaponType,
+    // Shots per second
+    pub fire_rate: f32,
+    // Units per second, on top of the ship's own velocity
+    pub projectile_speed: f32,
+    // Widest angle off the nose a shot can leave at, in radians
+    pub spread: f32,
+    // Distance a shot travels before it is recycled
+    pub range: f32,
+    pub damage: WeaponDamage,
+    pub supply: WeaponSupply,
+}
+
+impl Default for WeaponSettings {
+    fn default() -> WeaponSettings {
+        WeaponSettings {
+            weapon_type: WeaponType::Energy,
+            fire_rate: 8.0,
+            projectile_speed: 200.0,
+            spread: 0.01,
+            range: 400.0,
+            damage: WeaponDamage {
+                energy: 10.0,
+                physical: 2.0,
+            },
+            supply: WeaponSupply::Heat {
+                per_shot: 0.08,
+                cooling: 0.5,
+            },
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Weapon {
+    pub settings: WeaponSettings,
+    // Held down by whatever is flying the ship
+    pub trigger: bool,
+    pub heat: f32,
+    pub overheated: bool,
+    pub rounds: u32,
+    // Seconds until the next shot is allowed, carrying over the part of a tick already spent
+    cooldown: f32,
+    // Shots fired so far, picks each shot's place in the spread pattern
+    shots: u32,
+}
+
+impl Weapon {
+    pub fn new(settings: WeaponSettings) -> Weapon {
+        let rounds = match settings.supply {
+            WeaponSupply::Ammo(rounds) => rounds,
+            WeaponSupply::Heat { .. } => 0,
+        };
+        Weapon {
+            settings,
+            trigger: false,
+            heat: 0.0,
+            overheated: false,
+            rounds,
+            cooldown: 0.0,
+            shots: 0,
+        }
+    }
+
+    // Advances the weapon by `dt` and spends a shot if the trigger is held and one is ready
+    fn try_fire(&mut self, dt: f32) -> bool {
+        self.cooldown -= dt;
+        if let WeaponSupply::Heat { cooling, .. } = self.settings.supply {
+            self.heat = (self.heat - cooling * dt).max(0.0);
+            if self.heat == 0.0 {
+                self.overheated = false;
+            }
+        }
+        let loaded = match self.settings.supply {
+            WeaponSupply::Heat { .. } => !self.overheated,
+            WeaponSupply::Ammo(_) => self.rounds > 0,
+        };
+        if !self.trigger || !loaded || self.cooldown > 0.0 {
+            // Time spent idle does not bank up into a burst
+            self.cooldown = self.cooldown.max(0.0);
+            return false;
+        }
+        match self.settings.supply {
+            WeaponSupply::Heat { per_shot, .. } => {
+                self.heat += per_shot;
+                self.overheated = self.heat >= MAX_HEAT;
+            }
+            WeaponSupply::Ammo(_) => self.rounds -= 1,
+        }
+        self.cooldown += 1.0 / self.settings.fire_rate.max(f32::EPSILON);
+        self.shots += 1;
+        true
+    }
+}
+
+// Direction of the nth shot in the weapon's own space. Successive shots step around a golden
+// angle spiral, which fills the cone evenly and replays identically from run to run.
+fn spread_direction(shot: u32, spread: f32) -> Vec3 {
+    if spread <= 0.0 {
+        return Vec3::Z;
+    }
+    let shot = shot as f32;
+    let off_axis = spread * (shot * 0.618_034).fract().sqrt();
+    let around = shot * GOLDEN_ANGLE;
+    Quat::from_axis_angle(Vec3::new(around.cos(), around.sin(), 0.0), off_axis) * Vec3::Z
+}
+
+// A shot in flight. Parked projectiles in the pool have neither this nor a `Collider`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Projectile {
+    pub owner: Entity,
+    pub weapon_type: WeaponType,
+    pub damage: WeaponDamage,
+    pub velocity: Vec3,
+    pub range_left: f32,
+    // Set when it goes back to the pool. The component is only removed once the tick's commands
+    // apply, so a shot that hits and runs out on the same tick would otherwise be released twice.
+    spent: bool,
+}
+
+// Sent by the collision dispatch when a projectile touches a ship or scenery, other than the
+// ship that fired it
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectileHit {
+    pub projectile: Entity,
+    pub target: Entity,
+    pub owner: Entity,
+    pub weapon_type: WeaponType,
+    pub damage: WeaponDamage,
+}
+
+#[derive(Default, Resource)]
+pub struct ProjectilePool {
+    free: Vec<Entity>,
+}
+
+impl ProjectilePool {
+    fn release(&mut self, commands: &mut Commands, entity: Entity, projectile: &mut Projectile) {
+        if projectile.spent {
+            return;
+        }
+        projectile.spent = true;
+        commands
+            .entity(entity)
+            .remove::<(Projectile, Collider)>()
+            .insert(Visibility::INVISIBLE);
+        self.free.push(entity);
+    }
+}
+
+#[derive(Resource)]
+struct ProjectileAssets {
+    mesh: Handle<Mesh>,
+    energy: Handle<StandardMaterial>,
+    physical: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ProjectileAssets {
+    fn from_world(world: &mut World) -> ProjectileAssets {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(
+            shape::Capsule {
+                radius: PROJECTILE_RADIUS,
+                depth: 1.2,
+                ..default()
+            }
+            .into(),
+        );
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut glowing = |color: Color| {
+            materials.add(StandardMaterial {
+                base_color: color,
+                emissive: color,
+                unlit: true,
+                ..default()
+            })
+        };
+        ProjectileAssets {
+            mesh,
+            energy: glowing(Color::rgb(0.3, 0.9, 1.0)),
+            physical: glowing(Color::rgb(1.0, 0.75, 0.3)),
+        }
+    }
+}
+
+impl ProjectileAssets {
+    fn material(&self, weapon_type: WeaponType) -> Handle<StandardMaterial> {
+        match weapon_type {
+            WeaponType::Energy => self.energy.clone(),
+            WeaponType::Physical => self.physical.clone(),
+        }
+    }
+
+    fn spawn_parked(&self, commands: &mut Commands) -> Entity {
+        commands
+            .spawn((
+                PbrBundle {
+                    mesh: self.mesh.clone(),
+                    material: self.energy.clone(),
+                    visibility: Visibility::INVISIBLE,
+                    ..default()
+                },
+                SimulatedTransform::default(),
+                RigidBody::KinematicPositionBased,
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                // Ships are kinematic too and scenery is fixed, Rapier leaves both out by default
+                ActiveCollisionTypes::default()
+                    | ActiveCollisionTypes::KINEMATIC_KINEMATIC
+                    | ActiveCollisionTypes::KINEMATIC_STATIC,
+                OnGameScreen,
+            ))
+            .id()
+    }
+}
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProjectileAssets>()
+            .init_resource::<ProjectilePool>()
+            .add_event::<ProjectileHit>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(fill_projectile_pool))
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_run_criteria(simulation::game_running)
+                    .with_system(
+                        move_projectiles
+                            .label(SimulationLabel::Weapons)
+                            .after(SimulationLabel::Movement),
+                    )
+                    .with_system(
+                        fire_weapons
+                            .label(SimulationLabel::Weapons)
+                            .after(move_projectiles),
+                    )
+                    .with_system(recycle_spent_projectiles.after(collision::dispatch_collisions)),
+            );
+    }
+}
+
+// The previous level's projectiles went with its screen, so the pool starts over
+fn fill_projectile_pool(mut commands: Commands, assets: Res<ProjectileAssets>) {
+    let free = (0..POOL_PREWARM)
+        .map(|_| assets.spawn_parked(&mut commands))
+        .collect();
+    commands.insert_resource(ProjectilePool { free });
+}
+
+fn fire_weapons(
+    mut commands: Commands,
+    mut pool: ResMut<ProjectilePool>,
+    assets: Res<ProjectileAssets>,
+    mut sounds: EventWriter<SoundEffect>,
+    mut weapon_query: Query<(Entity, &mut Weapon, &SimulatedTransform)>,
+) {
+    for (owner, mut weapon, ship) in &mut weapon_query {
+        if !weapon.try_fire(SIMULATION_DT) {
+            continue;
+        }
+        let settings = &weapon.settings;
+        let direction = ship.current.rotation * spread_direction(weapon.shots, settings.spread);
+        // Ship speeds are kept in the rail's frame, this tick's movement is in the world's
+        let ship_velocity = (ship.current.translation - ship.previous.translation) / SIMULATION_DT;
+        let velocity = direction * settings.projectile_speed + ship_velocity;
+        let transform =
+            Transform::from_translation(ship.current.translation + direction * MUZZLE_OFFSET)
+                .with_rotation(Quat::from_rotation_arc(
+                    Vec3::Y,
+                    velocity.try_normalize().unwrap_or(direction),
+                ));
+
+        let entity = match pool.free.pop() {
+            Some(entity) => entity,
+            None => assets.spawn_parked(&mut commands),
+        };
+        commands.entity(entity).insert((
+            Projectile {
+                owner,
+                weapon_type: settings.weapon_type,
+                damage: settings.damage,
+                velocity,
+                range_left: settings.range,
+                spent: false,
+            },
+            SimulatedTransform::new(transform),
+            transform,
+            assets.material(settings.weapon_type),
+            Visibility::VISIBLE,
+            Collider::ball(PROJECTILE_RADIUS),
+        ));
+        sounds.send(SoundEffect::Fire);
+    }
+}
+
+fn move_projectiles(
+    mut commands: Commands,
+    mut pool: ResMut<ProjectilePool>,
+    mut query: Query<(Entity, &mut Projectile, &mut SimulatedTransform)>,
+) {
+    for (entity, mut projectile, mut simulated) in &mut query {
+        let step = projectile.velocity * SIMULATION_DT;
+        simulated.current.translation += step;
+        projectile.range_left -= step.length();
+        if projectile.range_left <= 0.0 {
+            pool.release(&mut commands, entity, &mut projectile);
+        }
+    }
+}
+
+// Shots that landed go back to the pool, the dispatch already made sure each lands once
+fn recycle_spent_projectiles(
+    mut commands: Commands,
+    mut pool: ResMut<ProjectilePool>,
+    mut hits: EventReader<ProjectileHit>,
+    mut projectile_query: Query<&mut Projectile>,
+) {
+    for hit in hits.iter() {
+        if let Ok(mut projectile) = projectile_query.get_mut(hit.projectile) {
+            pool.release(&mut commands, hit.projectile, &mut projectile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn energy_weapon() -> Weapon {
+        Weapon::new(WeaponSettings {
+            fire_rate: 10.0,
+            supply: WeaponSupply::Heat {
+                per_shot: 0.6,
+                cooling: 1.0,
+            },
+            ..WeaponSettings::default()
+        })
+    }
+
+    #[test]
+    fn fires_at_the_fire_rate() {
+        let mut weapon = energy_weapon();
+        weapon.settings.supply = WeaponSupply::Heat {
+            per_shot: 0.0,
+            cooling: 0.0,
+        };
+        weapon.trigger = true;
+        let shots = (0..60).filter(|_| weapon.try_fire(1.0 / 60.0)).count();
+        assert_eq!(shots, 10);
+    }
+
+    #[test]
+    fn overheated_weapon_waits_until_cool() {
+        let mut weapon = energy_weapon();
+        weapon.trigger = true;
+        assert!(weapon.try_fire(0.1));
+        assert!(weapon.try_fire(0.1));
+        assert!(weapon.overheated);
+        // Heat is down from 1.1 to 0.5, still locked until it reaches zero
+        assert!(!weapon.try_fire(0.6));
+        assert!(weapon.try_fire(0.6));
+    }
+
+    #[test]
+    fn ammo_runs_out() {
+        let mut weapon = Weapon::new(WeaponSettings {
+            supply: WeaponSupply::Ammo(2),
+            ..WeaponSettings::default()
+        });
+        weapon.trigger = true;
+        assert!(weapon.try_fire(1.0));
+        assert!(weapon.try_fire(1.0));
+        assert!(!weapon.try_fire(1.0));
+        assert_eq!(weapon.rounds, 0);
+    }
+
+    #[test]
+    fn spread_stays_inside_the_cone() {
+        let spread = 0.1;
+        for shot in 0..200 {
+            let direction = spread_direction(shot, spread);
+            assert!((direction.length() - 1.0).abs() < 1e-5);
+            assert!(direction.angle_between(Vec3::Z) <= spread + 1e-5);
+        }
+        assert_eq!(spread_direction(7, 0.0), Vec3::Z);
+    }
+}